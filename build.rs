@@ -0,0 +1,4 @@
+// Recompilar quando as migrações mudarem (elas são embutidas com sqlx::migrate!)
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
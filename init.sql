@@ -1,7 +1,8 @@
 -- Script de inicialização do banco de dados para Docker
 -- Este arquivo é executado automaticamente quando o container PostgreSQL é criado
 
--- Criar usuário padrão se não existir
+-- Criar usuário padrão (dono dos livros de exemplo, sem senha utilizável) se não existir.
+-- O primeiro administrador é criado com `rocket-postgres-tutorial create-admin`.
 INSERT INTO users (id, name, email, password_hash, age) VALUES 
 ('550e8400-e29b-41d4-a716-446655440000', 'Usuário Padrão', 'admin@example.com', '$2b$12$LQv3c1yqBWVHxkd0LHAkCOYz6TtxMQJqhN8/LewdBPj4J/5Qz8K2', 30)
 ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,64 @@
+-- Esquema inicial: usuários, categorias, livros e progresso de leitura.
+-- Usa IF NOT EXISTS para adotar bancos criados antes das migrações versionadas.
+
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    email VARCHAR(255) UNIQUE NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    age INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Bancos antigos receberam um valor padrão fictício para password_hash
+ALTER TABLE users ALTER COLUMN password_hash DROP DEFAULT;
+
+CREATE TABLE IF NOT EXISTS categories (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS books (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title VARCHAR(255) NOT NULL,
+    author VARCHAR(255) NOT NULL,
+    isbn VARCHAR(20),
+    description TEXT,
+    content TEXT NOT NULL,
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    is_public BOOLEAN DEFAULT false,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS reading_progress (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    book_id UUID NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    current_page INTEGER DEFAULT 0,
+    total_pages INTEGER DEFAULT 0,
+    is_completed BOOLEAN DEFAULT false,
+    last_read_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(user_id, book_id)
+);
+
+-- Categorias padrão
+INSERT INTO categories (name, description) VALUES 
+('Ficção', 'Livros de ficção e literatura'),
+('Não-Ficção', 'Livros informativos e educativos'),
+('Tecnologia', 'Livros sobre programação e tecnologia'),
+('História', 'Livros históricos e biografias'),
+('Ciência', 'Livros científicos e acadêmicos'),
+('Autoajuda', 'Livros de desenvolvimento pessoal'),
+('Romance', 'Livros românticos'),
+('Mistério', 'Livros de suspense e mistério'),
+('Fantasia', 'Livros de fantasia e ficção científica'),
+('Poesia', 'Coleções de poesia')
+ON CONFLICT (name) DO NOTHING;
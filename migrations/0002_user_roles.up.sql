@@ -0,0 +1,3 @@
+-- Papel do usuário: admin, author ou reader
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'author';
@@ -0,0 +1,17 @@
+-- Busca textual em português, ignorando acentos
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+CREATE TEXT SEARCH CONFIGURATION portuguese_unaccent (COPY = portuguese);
+ALTER TEXT SEARCH CONFIGURATION portuguese_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, portuguese_stem;
+
+-- Título e autor pesam mais que descrição, que pesa mais que o conteúdo
+ALTER TABLE books
+ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('portuguese_unaccent', coalesce(title, '')), 'A') ||
+    setweight(to_tsvector('portuguese_unaccent', coalesce(author, '')), 'A') ||
+    setweight(to_tsvector('portuguese_unaccent', coalesce(description, '')), 'B') ||
+    setweight(to_tsvector('portuguese_unaccent', coalesce(content, '')), 'C')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_books_search_vector ON books USING GIN (search_vector);
@@ -0,0 +1,17 @@
+-- Histórico de leitura: cada atualização de progresso registra as páginas lidas
+-- e se o livro foi concluído, para estatísticas por semana e por mês
+CREATE TABLE IF NOT EXISTS reading_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    book_id UUID NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    pages_read INTEGER NOT NULL DEFAULT 0,
+    finished BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_reading_log_user_created ON reading_log(user_id, created_at);
+
+-- Progresso existente entra no histórico na data da última leitura
+INSERT INTO reading_log (user_id, book_id, pages_read, finished, created_at)
+SELECT user_id, book_id, COALESCE(current_page, 0), COALESCE(is_completed, false), COALESCE(last_read_at, NOW())
+FROM reading_progress;
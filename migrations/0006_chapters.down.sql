@@ -0,0 +1,2 @@
+DROP FUNCTION IF EXISTS split_book_chapters(UUID);
+DROP TABLE IF EXISTS chapters;
@@ -0,0 +1,63 @@
+-- Capítulos dos livros, com ordem, título e conteúdo próprios
+CREATE TABLE IF NOT EXISTS chapters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    -- Adiável para permitir reordenar capítulos dentro de uma transação
+    CONSTRAINT chapters_book_position_key UNIQUE (book_id, position) DEFERRABLE INITIALLY DEFERRED
+);
+
+-- Recriar os capítulos de um livro a partir de books.content, dividindo nos
+-- títulos "Capítulo N: ...". Texto antes do primeiro título vira uma introdução;
+-- um livro sem títulos vira um único capítulo com o título do livro.
+CREATE OR REPLACE FUNCTION split_book_chapters(p_book_id UUID) RETURNS INTEGER AS $$
+DECLARE
+    heading CONSTANT TEXT := '(?:^|\n)[ \t]*Cap[ií]tulo[ \t]+\d+[ \t]*:[ \t]*([^\n]*)';
+    book_title TEXT;
+    book_content TEXT;
+    titles TEXT[];
+    bodies TEXT[];
+    pos INTEGER := 0;
+    i INTEGER;
+BEGIN
+    SELECT title, content INTO book_title, book_content FROM books WHERE id = p_book_id;
+    IF NOT FOUND THEN
+        RETURN 0;
+    END IF;
+
+    DELETE FROM chapters WHERE book_id = p_book_id;
+
+    SELECT array_agg(btrim(m[1]) ORDER BY ord) INTO titles
+    FROM regexp_matches(book_content, heading, 'g') WITH ORDINALITY AS t(m, ord);
+    bodies := regexp_split_to_array(book_content, heading);
+
+    IF titles IS NULL OR btrim(bodies[1], E' \t\r\n') <> '' THEN
+        pos := pos + 1;
+        INSERT INTO chapters (book_id, position, title, content)
+        VALUES (
+            p_book_id, pos,
+            CASE WHEN titles IS NULL THEN book_title ELSE 'Introdução' END,
+            btrim(bodies[1], E' \t\r\n')
+        );
+    END IF;
+
+    FOR i IN 1 .. coalesce(array_length(titles, 1), 0) LOOP
+        pos := pos + 1;
+        INSERT INTO chapters (book_id, position, title, content)
+        VALUES (
+            p_book_id, pos,
+            left(coalesce(nullif(titles[i], ''), 'Capítulo ' || i), 255),
+            btrim(bodies[i + 1], E' \t\r\n')
+        );
+    END LOOP;
+
+    RETURN pos;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Livros existentes
+SELECT split_book_chapters(id) FROM books;
@@ -0,0 +1,20 @@
+-- Histórico de revisões do conteúdo dos livros
+CREATE TABLE IF NOT EXISTS book_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    revision_number INTEGER NOT NULL,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    content TEXT NOT NULL,
+    word_count INTEGER NOT NULL DEFAULT 0,
+    word_delta INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE(book_id, revision_number)
+);
+
+-- Conteúdo atual dos livros existentes vira a primeira revisão
+INSERT INTO book_revisions (book_id, revision_number, author_id, content, word_count, word_delta, created_at)
+SELECT id, 1, user_id, content,
+       coalesce(array_length(regexp_split_to_array(nullif(btrim(content), ''), '\s+'), 1), 0),
+       coalesce(array_length(regexp_split_to_array(nullif(btrim(content), ''), '\s+'), 1), 0),
+       COALESCE(updated_at, NOW())
+FROM books;
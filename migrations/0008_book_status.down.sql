@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_books_scheduled_publish;
+ALTER TABLE books
+DROP COLUMN IF EXISTS scheduled_publish_at,
+DROP COLUMN IF EXISTS published_at,
+DROP COLUMN IF EXISTS status;
@@ -0,0 +1,11 @@
+-- Fluxo editorial: draft -> in_review -> published -> archived
+ALTER TABLE books
+ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'draft'
+    CHECK (status IN ('draft', 'in_review', 'published', 'archived')),
+ADD COLUMN IF NOT EXISTS published_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN IF NOT EXISTS scheduled_publish_at TIMESTAMP WITH TIME ZONE;
+
+-- Livros públicos existentes já estavam publicados
+UPDATE books SET status = 'published', published_at = COALESCE(created_at, NOW()) WHERE is_public = true;
+
+CREATE INDEX IF NOT EXISTS idx_books_scheduled_publish ON books(scheduled_publish_at) WHERE scheduled_publish_at IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Formato do conteúdo do livro: texto puro ou Markdown
+ALTER TABLE books
+ADD COLUMN IF NOT EXISTS content_format VARCHAR(20) NOT NULL DEFAULT 'plain'
+    CHECK (content_format IN ('plain', 'markdown'));
@@ -0,0 +1,5 @@
+ALTER TABLE books
+DROP COLUMN IF EXISTS page_count,
+DROP COLUMN IF EXISTS reading_time_minutes,
+DROP COLUMN IF EXISTS char_count,
+DROP COLUMN IF EXISTS word_count;
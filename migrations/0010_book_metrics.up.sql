@@ -0,0 +1,21 @@
+-- Métricas do conteúdo, recalculadas pelo banco sempre que books.content muda.
+-- Palavras são sequências de letras/dígitos (com hífen ou apóstrofo internos),
+-- o que ignora a marcação do Markdown. Leitura a 200 palavras por minuto e
+-- página canônica de 250 palavras.
+ALTER TABLE books
+ADD COLUMN word_count INTEGER GENERATED ALWAYS AS (
+    regexp_count(content, '[[:alnum:]]+([''’-][[:alnum:]]+)*')
+) STORED,
+ADD COLUMN char_count INTEGER GENERATED ALWAYS AS (char_length(content)) STORED,
+ADD COLUMN reading_time_minutes INTEGER GENERATED ALWAYS AS (
+    CEIL(regexp_count(content, '[[:alnum:]]+([''’-][[:alnum:]]+)*') / 200.0)::integer
+) STORED,
+ADD COLUMN page_count INTEGER GENERATED ALWAYS AS (
+    CEIL(regexp_count(content, '[[:alnum:]]+([''’-][[:alnum:]]+)*') / 250.0)::integer
+) STORED;
+
+-- Progresso registrado sem total de páginas passa a usar a contagem do livro
+UPDATE reading_progress rp
+SET total_pages = b.page_count
+FROM books b
+WHERE rp.book_id = b.id AND COALESCE(rp.total_pages, 0) = 0;
@@ -0,0 +1,25 @@
+-- Falhas de login seguidas por chave ("email:<email>" ou "ip:<endereço>").
+-- locked_until só é usado nas chaves de email (bloqueio temporário da conta).
+CREATE TABLE IF NOT EXISTS login_throttles (
+    key VARCHAR(320) PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMP WITH TIME ZONE
+);
+
+-- Histórico de bloqueios e desbloqueios de contas, consultado por administradores.
+-- user_id fica nulo quando o email bloqueado não pertence a nenhuma conta.
+CREATE TABLE IF NOT EXISTS account_lockout_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email VARCHAR(255) NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    event VARCHAR(20) NOT NULL CHECK (event IN ('locked', 'unlocked')),
+    -- Bloqueio: fim previsto e IP da última tentativa; desbloqueio: administrador responsável
+    locked_until TIMESTAMP WITH TIME ZONE,
+    ip_address VARCHAR(45),
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_lockout_events_created ON account_lockout_events(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_account_lockout_events_user ON account_lockout_events(user_id);
@@ -0,0 +1,17 @@
+-- Tokens de acesso pessoal para scripts e integrações (apenas o hash SHA-256 é salvo)
+CREATE TABLE IF NOT EXISTS personal_access_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    -- Início do token em claro, para o usuário reconhecê-lo na listagem
+    token_prefix VARCHAR(16) NOT NULL,
+    scopes VARCHAR(20)[] NOT NULL CHECK (
+        cardinality(scopes) > 0 AND scopes <@ ARRAY['books:read', 'books:write', 'admin']::VARCHAR(20)[]
+    ),
+    expires_at TIMESTAMP WITH TIME ZONE,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_personal_access_tokens_user ON personal_access_tokens(user_id);
@@ -0,0 +1,2 @@
+-- O valor fictício original não é restaurado: ele também não permitia login
+SELECT 1;
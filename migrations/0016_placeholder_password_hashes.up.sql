@@ -0,0 +1,4 @@
+-- Bancos antigos têm contas com o valor padrão fictício 'senha_padrao_hash',
+-- que não é um hash bcrypt. Marcar com um hash inutilizável: essas contas não
+-- entram com senha até redefini-la pelo link enviado por email.
+UPDATE users SET password_hash = '!' WHERE password_hash = 'senha_padrao_hash';
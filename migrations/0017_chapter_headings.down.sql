@@ -0,0 +1,49 @@
+ALTER TABLE chapters DROP COLUMN IF EXISTS has_heading;
+
+-- Recriar os capítulos de um livro a partir de books.content, dividindo nos
+-- títulos "Capítulo N: ...". Texto antes do primeiro título vira uma introdução;
+-- um livro sem títulos vira um único capítulo com o título do livro.
+CREATE OR REPLACE FUNCTION split_book_chapters(p_book_id UUID) RETURNS INTEGER AS $$
+DECLARE
+    heading CONSTANT TEXT := '(?:^|\n)[ \t]*Cap[ií]tulo[ \t]+\d+[ \t]*:[ \t]*([^\n]*)';
+    book_title TEXT;
+    book_content TEXT;
+    titles TEXT[];
+    bodies TEXT[];
+    pos INTEGER := 0;
+    i INTEGER;
+BEGIN
+    SELECT title, content INTO book_title, book_content FROM books WHERE id = p_book_id;
+    IF NOT FOUND THEN
+        RETURN 0;
+    END IF;
+
+    DELETE FROM chapters WHERE book_id = p_book_id;
+
+    SELECT array_agg(btrim(m[1]) ORDER BY ord) INTO titles
+    FROM regexp_matches(book_content, heading, 'g') WITH ORDINALITY AS t(m, ord);
+    bodies := regexp_split_to_array(book_content, heading);
+
+    IF titles IS NULL OR btrim(bodies[1], E' \t\r\n') <> '' THEN
+        pos := pos + 1;
+        INSERT INTO chapters (book_id, position, title, content)
+        VALUES (
+            p_book_id, pos,
+            CASE WHEN titles IS NULL THEN book_title ELSE 'Introdução' END,
+            btrim(bodies[1], E' \t\r\n')
+        );
+    END IF;
+
+    FOR i IN 1 .. coalesce(array_length(titles, 1), 0) LOOP
+        pos := pos + 1;
+        INSERT INTO chapters (book_id, position, title, content)
+        VALUES (
+            p_book_id, pos,
+            left(coalesce(nullif(titles[i], ''), 'Capítulo ' || i), 255),
+            btrim(bodies[i + 1], E' \t\r\n')
+        );
+    END LOOP;
+
+    RETURN pos;
+END;
+$$ LANGUAGE plpgsql;
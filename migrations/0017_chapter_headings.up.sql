@@ -0,0 +1,85 @@
+-- Capítulos sem título "Capítulo N: ..." no texto do livro: a introdução antes
+-- do primeiro título e o capítulo único de um livro sem títulos
+ALTER TABLE chapters ADD COLUMN IF NOT EXISTS has_heading BOOLEAN NOT NULL DEFAULT TRUE;
+
+UPDATE chapters c SET has_heading = FALSE
+FROM books b
+WHERE c.book_id = b.id
+  AND c.position = 1
+  AND b.content !~ '^\s*Cap[ií]tulo[ \t]+\d+[ \t]*:';
+
+-- Sincronizar os capítulos de um livro com books.content, dividindo nos
+-- títulos "Capítulo N: ...". Capítulos com o mesmo título são atualizados no
+-- lugar (mantendo o id); os que sumiram do texto são removidos. Texto antes do
+-- primeiro título vira uma introdução; um livro sem títulos vira um único
+-- capítulo com o título do livro.
+CREATE OR REPLACE FUNCTION split_book_chapters(p_book_id UUID) RETURNS INTEGER AS $$
+DECLARE
+    heading CONSTANT TEXT := '(?:^|\n)[ \t]*Cap[ií]tulo[ \t]+\d+[ \t]*:[ \t]*([^\n]*)';
+    book_title TEXT;
+    book_content TEXT;
+    titles TEXT[];
+    bodies TEXT[];
+    new_titles TEXT[] := '{}';
+    new_bodies TEXT[] := '{}';
+    new_headings BOOLEAN[] := '{}';
+    kept UUID[] := '{}';
+    chapter_id UUID;
+    i INTEGER;
+BEGIN
+    SELECT title, content INTO book_title, book_content FROM books WHERE id = p_book_id;
+    IF NOT FOUND THEN
+        RETURN 0;
+    END IF;
+
+    SELECT array_agg(btrim(m[1]) ORDER BY ord) INTO titles
+    FROM regexp_matches(book_content, heading, 'g') WITH ORDINALITY AS t(m, ord);
+    bodies := regexp_split_to_array(book_content, heading);
+
+    IF titles IS NULL OR btrim(bodies[1], E' \t\r\n') <> '' THEN
+        new_titles := new_titles || left(CASE WHEN titles IS NULL THEN book_title ELSE 'Introdução' END, 255);
+        new_bodies := new_bodies || btrim(bodies[1], E' \t\r\n');
+        new_headings := new_headings || FALSE;
+    END IF;
+
+    FOR i IN 1 .. coalesce(array_length(titles, 1), 0) LOOP
+        new_titles := new_titles || left(coalesce(nullif(titles[i], ''), 'Capítulo ' || i), 255);
+        new_bodies := new_bodies || btrim(bodies[i + 1], E' \t\r\n');
+        new_headings := new_headings || TRUE;
+    END LOOP;
+
+    FOR i IN 1 .. coalesce(array_length(new_titles, 1), 0) LOOP
+        -- O capítulo sem título casa com o anterior sem título, mesmo que o
+        -- título do livro tenha mudado
+        SELECT id INTO chapter_id
+        FROM chapters
+        WHERE book_id = p_book_id
+          AND has_heading = new_headings[i]
+          AND (NOT new_headings[i] OR title = new_titles[i])
+          AND id <> ALL(kept)
+        ORDER BY position
+        LIMIT 1;
+
+        IF FOUND THEN
+            UPDATE chapters SET
+                position = i,
+                title = new_titles[i],
+                content = new_bodies[i],
+                updated_at = CASE
+                    WHEN title = new_titles[i] AND content = new_bodies[i] THEN updated_at
+                    ELSE NOW()
+                END
+            WHERE id = chapter_id;
+        ELSE
+            INSERT INTO chapters (book_id, position, title, content, has_heading)
+            VALUES (p_book_id, i, new_titles[i], new_bodies[i], new_headings[i])
+            RETURNING id INTO chapter_id;
+        END IF;
+        kept := kept || chapter_id;
+    END LOOP;
+
+    DELETE FROM chapters WHERE book_id = p_book_id AND id <> ALL(kept);
+
+    RETURN coalesce(array_length(new_titles, 1), 0);
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,2 @@
+-- A contagem antiga (por espaços) não é restaurada
+SELECT 1;
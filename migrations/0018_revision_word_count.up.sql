@@ -0,0 +1,13 @@
+-- Revisões antigas contavam palavras separando por espaços; recalcular com a
+-- mesma regra de books.word_count para que os deltas sejam comparáveis
+UPDATE book_revisions r
+SET word_count = counted.word_count,
+    word_delta = counted.word_count - counted.previous_count
+FROM (
+    SELECT id,
+           regexp_count(content, '[[:alnum:]]+([''’-][[:alnum:]]+)*') AS word_count,
+           COALESCE(LAG(regexp_count(content, '[[:alnum:]]+([''’-][[:alnum:]]+)*'))
+               OVER (PARTITION BY book_id ORDER BY revision_number), 0) AS previous_count
+    FROM book_revisions
+) counted
+WHERE r.id = counted.id;
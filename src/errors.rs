@@ -0,0 +1,143 @@
+use rocket::{
+    catch,
+    http::Status,
+    request::Request,
+    response::{self, Responder},
+    serde::json::Json,
+};
+use std::fmt::Display;
+use crate::models::{ApiResponse, FieldError};
+
+// SQLSTATE do PostgreSQL para violações de restrição
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+// Erro da aplicação: status HTTP, código legível por máquina e mensagem para o usuário
+#[derive(Debug, Clone)]
+pub struct AppError {
+    pub status: Status,
+    pub code: &'static str,
+    pub message: String,
+    pub fields: Option<Vec<FieldError>>,
+    // Segundos até poder tentar de novo (cabeçalho Retry-After das respostas 429)
+    pub retry_after: Option<u64>,
+}
+
+// Resultado padrão dos handlers da API
+pub type ApiResult<T> = Result<Json<ApiResponse<T>>, AppError>;
+
+impl AppError {
+    pub fn new(status: Status, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            fields: None,
+            retry_after: None,
+        }
+    }
+
+    // Anexar erros por campo (ex.: falhas de validação)
+    pub fn with_fields(mut self, fields: Vec<FieldError>) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Status::BadRequest, code, message)
+    }
+
+    pub fn unauthorized(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Status::Unauthorized, code, message)
+    }
+
+    pub fn forbidden(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Status::Forbidden, code, message)
+    }
+
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Status::NotFound, code, message)
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Status::Conflict, code, message)
+    }
+
+    pub fn unprocessable(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Status::UnprocessableEntity, code, message)
+    }
+
+    // Limite de tentativas atingido; o cliente deve esperar os segundos informados
+    pub fn too_many_requests(code: &'static str, message: impl Into<String>, retry_after_seconds: i64) -> Self {
+        let mut error = Self::new(Status::TooManyRequests, code, message);
+        error.retry_after = Some(retry_after_seconds.max(1) as u64);
+        error
+    }
+
+    // Erro inesperado: registra o detalhe no log e devolve uma mensagem genérica
+    pub fn internal(context: &str, error: impl Display) -> Self {
+        log_error!("{}: {}", context, error);
+        Self::new(Status::InternalServerError, "INTERNAL_ERROR", "Erro interno do servidor")
+    }
+
+    // ID inválido recebido na URL
+    pub fn invalid_id() -> Self {
+        Self::bad_request("INVALID_ID", "Identificador inválido")
+    }
+}
+
+impl<'r> Responder<'r, 'static> for AppError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = ApiResponse::<()>::error(self.code, &self.message);
+        response.errors = self.fields;
+        let body = Json(response);
+        let mut http_response = (self.status, body).respond_to(request)?;
+        if let Some(seconds) = self.retry_after {
+            http_response.set_raw_header("Retry-After", seconds.to_string());
+        }
+        Ok(http_response)
+    }
+}
+
+// Verifica se o erro é violação de unicidade (ex.: email duplicado)
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some(UNIQUE_VIOLATION))
+}
+
+// Verifica se o erro é violação de chave estrangeira (ex.: categoria inexistente)
+pub fn is_foreign_key_violation(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some(FOREIGN_KEY_VIOLATION))
+}
+
+// Erro registrado por um request guard para ser devolvido pelo catcher
+#[derive(Default)]
+pub struct GuardError(pub Option<AppError>);
+
+// Marca o erro do guard na requisição e devolve o status para o Rocket
+pub fn guard_error(request: &Request<'_>, error: AppError) -> (Status, ()) {
+    let status = error.status;
+    request.local_cache(|| GuardError(Some(error)));
+    (status, ())
+}
+
+// Catcher padrão: toda resposta de erro sai como ApiResponse em JSON
+#[catch(default)]
+pub fn default_catcher(status: Status, request: &Request<'_>) -> AppError {
+    if let Some(error) = &request.local_cache(GuardError::default).0 {
+        return error.clone();
+    }
+
+    let (code, message) = match status.code {
+        400 => ("BAD_REQUEST", "Requisição inválida"),
+        401 => ("UNAUTHORIZED", "Autenticação necessária"),
+        403 => ("FORBIDDEN", "Acesso negado"),
+        404 => ("NOT_FOUND", "Recurso não encontrado"),
+        413 => ("PAYLOAD_TOO_LARGE", "Conteúdo muito grande"),
+        415 => ("UNSUPPORTED_MEDIA_TYPE", "Tipo de conteúdo não suportado"),
+        422 => ("UNPROCESSABLE_ENTITY", "Corpo da requisição inválido"),
+        429 => ("TOO_MANY_REQUESTS", "Muitas requisições"),
+        500.. => ("INTERNAL_ERROR", "Erro interno do servidor"),
+        _ => ("ERROR", "Erro na requisição"),
+    };
+    AppError::new(status, code, message)
+}
@@ -0,0 +1,429 @@
+use std::io::{Cursor, Write};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+use crate::{
+    export::{escape_xml, paragraphs},
+    models::book::{BookWithCategory, Chapter, ContentFormat},
+    render::render_xhtml,
+};
+
+const LANGUAGE: &str = "pt-BR";
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const STYLESHEET: &str = r#"body { font-family: serif; line-height: 1.5; margin: 0 5%; }
+h1 { text-align: center; margin: 2em 0 1em; }
+p { text-indent: 1.5em; margin: 0 0 0.5em; text-align: justify; }
+.title-page { text-align: center; margin-top: 20%; }
+.title-page p { text-indent: 0; text-align: center; }
+.author { font-size: 1.2em; font-style: italic; }
+.description { margin-top: 2em; }
+"#;
+
+// Nome do arquivo XHTML do capítulo dentro do pacote
+fn chapter_file(index: usize) -> String {
+    format!("chapter-{:03}.xhtml", index + 1)
+}
+
+// Documento XHTML com o cabeçalho comum a todas as páginas do livro
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head>
+  <meta charset="UTF-8"/>
+  <title>{title}</title>
+  <link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        lang = LANGUAGE,
+        title = escape_xml(title),
+        body = body
+    )
+}
+
+// Parágrafos do texto em <p>, com quebras de linha simples preservadas
+fn paragraphs_html(text: &str) -> String {
+    paragraphs(text)
+        .iter()
+        .map(|lines| {
+            let lines: Vec<String> = lines.iter().map(|line| escape_xml(line.trim())).collect();
+            format!("    <p>{}</p>\n", lines.join("<br/>"))
+        })
+        .collect()
+}
+
+fn title_page(book: &BookWithCategory) -> String {
+    let description = book
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| format!("    <div class=\"description\">\n{}    </div>\n", paragraphs_html(d)))
+        .unwrap_or_default();
+
+    xhtml_document(
+        &book.title,
+        &format!(
+            "  <section epub:type=\"titlepage\" class=\"title-page\">\n    <h1>{}</h1>\n    <p class=\"author\">{}</p>\n{}  </section>",
+            escape_xml(&book.title),
+            escape_xml(&book.author),
+            description
+        ),
+    )
+}
+
+// Capítulo no formato do livro (texto puro ou Markdown), como na leitura em HTML
+fn chapter_page(chapter: &Chapter, format: ContentFormat) -> String {
+    xhtml_document(
+        &chapter.title,
+        &format!(
+            "  <section epub:type=\"chapter\" id=\"chapter-{}\">\n    <h1>{}</h1>\n{}  </section>",
+            chapter.position,
+            escape_xml(&chapter.title),
+            render_xhtml(&chapter.content, format, &format!("c{}-", chapter.position))
+        ),
+    )
+}
+
+// Documento de navegação do EPUB 3 (sumário e pontos de referência)
+fn nav_document(chapters: &[Chapter]) -> String {
+    let items: String = chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            format!("        <li><a href=\"{}\">{}</a></li>\n", chapter_file(i), escape_xml(&chapter.title))
+        })
+        .collect();
+
+    xhtml_document(
+        "Sumário",
+        &format!(
+            r#"  <nav epub:type="toc" id="toc">
+    <h1>Sumário</h1>
+    <ol>
+{items}    </ol>
+  </nav>
+  <nav epub:type="landmarks" id="landmarks" hidden="hidden">
+    <ol>
+      <li><a epub:type="titlepage" href="title.xhtml">Folha de rosto</a></li>
+      <li><a epub:type="bodymatter" href="{first}">Início</a></li>
+    </ol>
+  </nav>"#,
+            items = items,
+            first = chapter_file(0)
+        ),
+    )
+}
+
+// Identificador único do pacote: ISBN quando houver, senão o ID do livro
+fn identifier(book: &BookWithCategory) -> String {
+    let isbn: Option<String> = book
+        .isbn
+        .as_deref()
+        .map(|isbn| isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect())
+        .filter(|isbn: &String| !isbn.is_empty());
+
+    match isbn {
+        Some(isbn) => format!("urn:isbn:{}", isbn),
+        None => format!("urn:uuid:{}", book.id),
+    }
+}
+
+fn package_document(book: &BookWithCategory, chapters: &[Chapter]) -> String {
+    let mut metadata = vec![
+        format!("    <dc:identifier id=\"book-id\">{}</dc:identifier>", escape_xml(&identifier(book))),
+        format!("    <dc:title>{}</dc:title>", escape_xml(&book.title)),
+        format!("    <dc:language>{}</dc:language>", LANGUAGE),
+        format!("    <dc:creator id=\"creator\">{}</dc:creator>", escape_xml(&book.author)),
+        "    <meta refines=\"#creator\" property=\"role\" scheme=\"marc:relators\">aut</meta>".to_string(),
+        format!("    <dc:subject>{}</dc:subject>", escape_xml(&book.cat_name)),
+    ];
+    if let Some(description) = book.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        metadata.push(format!("    <dc:description>{}</dc:description>", escape_xml(description.trim())));
+    }
+    if let Some(published_at) = book.published_at {
+        metadata.push(format!("    <dc:date>{}</dc:date>", published_at.format("%Y-%m-%d")));
+    }
+    metadata.push(format!(
+        "    <meta property=\"dcterms:modified\">{}</meta>",
+        book.updated_at.format("%Y-%m-%dT%H:%M:%SZ")
+    ));
+
+    let mut manifest = vec![
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>".to_string(),
+        "    <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>".to_string(),
+        "    <item id=\"title\" href=\"title.xhtml\" media-type=\"application/xhtml+xml\"/>".to_string(),
+    ];
+    let mut spine = vec!["    <itemref idref=\"title\"/>".to_string()];
+    for i in 0..chapters.len() {
+        manifest.push(format!(
+            "    <item id=\"chapter-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>",
+            i + 1,
+            chapter_file(i)
+        ));
+        spine.push(format!("    <itemref idref=\"chapter-{}\"/>", i + 1));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{lang}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{metadata}
+  </metadata>
+  <manifest>
+{manifest}
+  </manifest>
+  <spine>
+{spine}
+  </spine>
+</package>
+"#,
+        lang = LANGUAGE,
+        metadata = metadata.join("\n"),
+        manifest = manifest.join("\n"),
+        spine = spine.join("\n")
+    )
+}
+
+// Montar o pacote EPUB 3 com uma página XHTML por capítulo.
+// Um livro sem capítulos vira um único capítulo com o conteúdo completo.
+pub fn build_epub(book: &BookWithCategory, chapters: &[Chapter]) -> zip::result::ZipResult<Vec<u8>> {
+    let fallback;
+    let chapters = if chapters.is_empty() {
+        fallback = [Chapter {
+            id: book.id,
+            book_id: book.id,
+            position: 1,
+            title: book.title.clone(),
+            content: book.content.clone(),
+            created_at: book.created_at,
+            updated_at: book.updated_at,
+        }];
+        &fallback[..]
+    } else {
+        chapters
+    };
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // O mimetype deve ser o primeiro arquivo, sem compressão
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(CONTAINER_XML.as_bytes())?;
+
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(package_document(book, chapters).as_bytes())?;
+
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(nav_document(chapters).as_bytes())?;
+
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(STYLESHEET.as_bytes())?;
+
+    zip.start_file("OEBPS/title.xhtml", deflated)?;
+    zip.write_all(title_page(book).as_bytes())?;
+
+    for (i, chapter) in chapters.iter().enumerate() {
+        zip.start_file(format!("OEBPS/{}", chapter_file(i)), deflated)?;
+        zip.write_all(chapter_page(chapter, book.content_format).as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{test_book as book, test_chapter as chapter};
+    use std::io::Read;
+    use uuid::Uuid;
+    use zip::ZipArchive;
+
+    const OPF_NS: &str = "http://www.idpf.org/2007/opf";
+    const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+
+    // As páginas XHTML têm <!DOCTYPE html>
+    fn parse(xml: &str) -> Result<roxmltree::Document<'_>, roxmltree::Error> {
+        roxmltree::Document::parse_with_options(
+            xml,
+            roxmltree::ParsingOptions { allow_dtd: true, ..roxmltree::ParsingOptions::default() },
+        )
+    }
+
+    fn open(bytes: Vec<u8>) -> ZipArchive<Cursor<Vec<u8>>> {
+        ZipArchive::new(Cursor::new(bytes)).unwrap()
+    }
+
+    fn read(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut text = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+
+    // Itens do manifesto (id -> href) e idrefs da espinha, na ordem
+    fn manifest_and_spine(opf: &str) -> (Vec<(String, String)>, Vec<String>) {
+        let doc = parse(opf).unwrap();
+        let items = doc
+            .descendants()
+            .filter(|n| n.has_tag_name((OPF_NS, "item")))
+            .map(|n| (n.attribute("id").unwrap().to_string(), n.attribute("href").unwrap().to_string()))
+            .collect();
+        let spine = doc
+            .descendants()
+            .filter(|n| n.has_tag_name((OPF_NS, "itemref")))
+            .map(|n| n.attribute("idref").unwrap().to_string())
+            .collect();
+        (items, spine)
+    }
+
+    // Recursos (src, link href) das páginas XHTML que não estão no manifesto ou são remotos
+    fn unresolved_resources(bytes: Vec<u8>) -> Vec<String> {
+        let mut archive = open(bytes);
+        let (items, _) = manifest_and_spine(&read(&mut archive, "OEBPS/content.opf"));
+        let mut unresolved = Vec::new();
+        for (_, href) in items.iter().filter(|(_, href)| href.ends_with(".xhtml")) {
+            let page = read(&mut archive, &format!("OEBPS/{}", href));
+            let doc = parse(&page).unwrap();
+            for node in doc.descendants().filter(|n| n.is_element()) {
+                let resource = node
+                    .attribute("src")
+                    .or_else(|| node.has_tag_name((XHTML_NS, "link")).then(|| node.attribute("href")).flatten());
+                if let Some(resource) = resource {
+                    if !items.iter().any(|(_, item)| item == resource) {
+                        unresolved.push(resource.to_string());
+                    }
+                }
+            }
+        }
+        unresolved
+    }
+
+    #[test]
+    fn mimetype_is_the_first_entry_and_stored() {
+        let mut archive = open(build_epub(&book(ContentFormat::Plain), &[]).unwrap());
+        let mut mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+
+        let mut content = String::new();
+        mimetype.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "application/epub+zip");
+    }
+
+    #[test]
+    fn container_and_manifest_cover_every_file() {
+        let chapters = [chapter(1, "Um", "Texto um."), chapter(2, "Dois", "Texto dois.")];
+        let mut archive = open(build_epub(&book(ContentFormat::Plain), &chapters).unwrap());
+
+        let container = read(&mut archive, "META-INF/container.xml");
+        let container = parse(&container).unwrap();
+        let rootfile = container.descendants().find(|n| n.has_tag_name("rootfile")).unwrap();
+        assert_eq!(rootfile.attribute("full-path"), Some("OEBPS/content.opf"));
+        assert_eq!(rootfile.attribute("media-type"), Some("application/oebps-package+xml"));
+
+        let opf = read(&mut archive, "OEBPS/content.opf");
+        let (items, _) = manifest_and_spine(&opf);
+        let mut manifest: Vec<String> = items.iter().map(|(_, href)| format!("OEBPS/{}", href)).collect();
+        manifest.extend(["mimetype", "META-INF/container.xml", "OEBPS/content.opf"].map(String::from));
+        manifest.sort();
+        let mut files: Vec<String> = archive.file_names().map(String::from).collect();
+        files.sort();
+        assert_eq!(files, manifest);
+
+        let package = parse(&opf).unwrap();
+        let identifier = package.descendants().find(|n| n.has_tag_name("identifier")).unwrap();
+        assert_eq!(package.root_element().attribute("unique-identifier"), identifier.attribute("id"));
+        assert_eq!(identifier.text(), Some("urn:isbn:9780306406157"));
+        let nav_items = package.descendants().filter(|n| n.attribute("properties") == Some("nav")).count();
+        assert_eq!(nav_items, 1);
+    }
+
+    #[test]
+    fn nav_lists_the_chapters_in_spine_order() {
+        let chapters = [chapter(1, "Um", "a"), chapter(2, "Dois", "b"), chapter(3, "Três", "c")];
+        let mut archive = open(build_epub(&book(ContentFormat::Plain), &chapters).unwrap());
+        let (items, spine) = manifest_and_spine(&read(&mut archive, "OEBPS/content.opf"));
+        let spine: Vec<&str> = spine
+            .iter()
+            .map(|idref| items.iter().find(|(id, _)| id == idref).map(|(_, href)| href.as_str()).unwrap())
+            .collect();
+
+        let nav = read(&mut archive, "OEBPS/nav.xhtml");
+        let nav = parse(&nav).unwrap();
+        let toc = nav.descendants().find(|n| n.attribute("id") == Some("toc")).unwrap();
+        let links: Vec<(&str, &str)> = toc
+            .descendants()
+            .filter(|n| n.has_tag_name((XHTML_NS, "a")))
+            .map(|a| (a.attribute("href").unwrap(), a.text().unwrap()))
+            .collect();
+
+        assert_eq!(spine[0], "title.xhtml");
+        assert_eq!(links.iter().map(|(href, _)| *href).collect::<Vec<_>>(), &spine[1..]);
+        assert_eq!(links.iter().map(|(_, title)| *title).collect::<Vec<_>>(), vec!["Um", "Dois", "Três"]);
+    }
+
+    #[test]
+    fn pages_are_well_formed_xhtml() {
+        let chapters = [
+            chapter(1, "A < B", "Linha & outra\ncontinua.\n\nNovo parágrafo."),
+            chapter(2, "Markdown", "*ênfase* e <b>html</b>&nbsp;aqui  \nquebra\n\n---\n\nNota[^1]\n\n[^1]: texto"),
+        ];
+        let mut archive = open(build_epub(&book(ContentFormat::Markdown), &chapters).unwrap());
+
+        for name in ["OEBPS/nav.xhtml", "OEBPS/title.xhtml", "OEBPS/chapter-001.xhtml", "OEBPS/chapter-002.xhtml"] {
+            let page = read(&mut archive, name);
+            let doc = parse(&page).unwrap_or_else(|e| panic!("{}: {}\n{}", name, e, page));
+            assert!(doc.root_element().has_tag_name((XHTML_NS, "html")), "{}", name);
+        }
+
+        let title = read(&mut archive, "OEBPS/chapter-001.xhtml");
+        let title = parse(&title).unwrap();
+        let heading = title.descendants().find(|n| n.has_tag_name((XHTML_NS, "h1"))).unwrap();
+        assert_eq!(heading.text(), Some("A < B"));
+    }
+
+    #[test]
+    fn book_without_chapters_becomes_a_single_chapter() {
+        let mut archive = open(build_epub(&book(ContentFormat::Plain), &[]).unwrap());
+        let (items, spine) = manifest_and_spine(&read(&mut archive, "OEBPS/content.opf"));
+        assert_eq!(spine, vec!["title", "chapter-1"]);
+        assert!(items.iter().any(|(_, href)| href == "chapter-001.xhtml"));
+        assert!(read(&mut archive, "OEBPS/chapter-001.xhtml").contains("<p>Texto completo.</p>"));
+    }
+
+    #[test]
+    fn pages_only_reference_packaged_resources() {
+        let chapters = [chapter(1, "Um", "Texto.")];
+        assert!(unresolved_resources(build_epub(&book(ContentFormat::Plain), &chapters).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn markdown_images_do_not_leave_unresolved_resources() {
+        let chapters = [chapter(1, "Um", "![x](foo.png)\n\nTexto <img src=\"https://exemplo.com/a.png\"/>.")];
+        let bytes = build_epub(&book(ContentFormat::Markdown), &chapters).unwrap();
+        assert!(read(&mut open(bytes.clone()), "OEBPS/chapter-001.xhtml").contains("<p>x</p>"));
+        assert_eq!(unresolved_resources(bytes), Vec::<String>::new());
+    }
+
+    #[test]
+    fn identifier_falls_back_to_the_book_id() {
+        let mut book = book(ContentFormat::Plain);
+        assert_eq!(identifier(&book), "urn:isbn:9780306406157");
+        book.isbn = Some(" - ".to_string());
+        assert_eq!(identifier(&book), format!("urn:uuid:{}", Uuid::nil()));
+    }
+}
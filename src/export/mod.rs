@@ -0,0 +1,140 @@
+// Exportação de livros para formatos de distribuição
+pub mod epub;
+pub mod pdf;
+
+use rocket::http::{ContentType, Header};
+use rocket::response::{self, Responder, Response};
+use rocket::Request;
+use std::io::Cursor;
+
+// Arquivo gerado, enviado como download
+pub struct ExportFile {
+    pub content_type: ContentType,
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+impl<'r> Responder<'r, 'static> for ExportFile {
+    fn respond_to(self, _request: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .header(self.content_type)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.filename),
+            ))
+            .sized_body(self.bytes.len(), Cursor::new(self.bytes))
+            .ok()
+    }
+}
+
+// Nome de arquivo seguro a partir do título (sem acentos, só [a-z0-9-])
+pub fn file_stem(title: &str) -> String {
+    let mut stem = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            c => c,
+        };
+        if c.is_ascii_alphanumeric() {
+            stem.push(c);
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    if stem.is_empty() {
+        "livro".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+// Escapar texto para uso em XML/XHTML
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Caracteres de controle não são permitidos em XML 1.0
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Parágrafos do texto: blocos separados por linhas em branco
+pub fn paragraphs(text: &str) -> Vec<Vec<&str>> {
+    let mut result = Vec::new();
+    let mut current = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                result.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+// Livro e capítulo de exemplo para os testes dos exportadores
+#[cfg(test)]
+fn test_book(content_format: crate::models::book::ContentFormat) -> crate::models::book::BookWithCategory {
+    let now = chrono::Utc::now();
+    crate::models::book::BookWithCategory {
+        id: uuid::Uuid::nil(),
+        title: "O Livro & Cia".to_string(),
+        author: "Ana".to_string(),
+        isbn: Some("978-0-306-40615-7".to_string()),
+        description: Some("Sobre <tudo>.".to_string()),
+        content: "Texto completo.".to_string(),
+        content_format,
+        category_id: uuid::Uuid::nil(),
+        user_id: uuid::Uuid::nil(),
+        is_public: true,
+        status: Default::default(),
+        published_at: None,
+        scheduled_publish_at: None,
+        word_count: 2,
+        char_count: 15,
+        reading_time_minutes: 1,
+        page_count: 1,
+        created_at: now,
+        updated_at: now,
+        cat_id: uuid::Uuid::nil(),
+        cat_name: "Ficção".to_string(),
+        cat_description: None,
+        cat_created_at: now,
+        cat_updated_at: now,
+    }
+}
+
+#[cfg(test)]
+fn test_chapter(position: i32, title: &str, content: &str) -> crate::models::book::Chapter {
+    let now = chrono::Utc::now();
+    crate::models::book::Chapter {
+        id: uuid::Uuid::nil(),
+        book_id: uuid::Uuid::nil(),
+        position,
+        title: title.to_string(),
+        content: content.to_string(),
+        created_at: now,
+        updated_at: now,
+    }
+}
@@ -0,0 +1,588 @@
+use printpdf::{IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+use ttf_parser::Face;
+use crate::{
+    export::paragraphs,
+    models::book::{BookWithCategory, Chapter, ContentFormat},
+    render::render_plain_text,
+};
+
+// Formatos de página aceitos (largura x altura em mm)
+pub const TRIM_SIZES: &[(&str, f32, f32)] = &[
+    ("a4", 210.0, 297.0),
+    ("a5", 148.0, 210.0),
+    ("letter", 215.9, 279.4),
+    ("5x8", 127.0, 203.2),
+    ("5.5x8.5", 139.7, 215.9),
+    ("6x9", 152.4, 228.6),
+];
+
+// Tamanho mínimo da mancha de texto depois de descontar as margens
+const MIN_TEXT_WIDTH: f32 = 50.0;
+const MIN_TEXT_HEIGHT: f32 = 80.0;
+
+const PT_TO_MM: f32 = 25.4 / 72.0;
+
+pub fn trim_size(name: &str) -> Option<(f32, f32)> {
+    TRIM_SIZES
+        .iter()
+        .find(|(trim, _, _)| trim.eq_ignore_ascii_case(name))
+        .map(|(_, width, height)| (*width, *height))
+}
+
+// Opções de layout (medidas em mm, exceto o corpo do texto em pt).
+// Margem interna fica do lado da lombada: à esquerda nas páginas ímpares
+// (recto) e à direita nas pares (verso).
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    pub page_width: f32,
+    pub page_height: f32,
+    pub margin_top: f32,
+    pub margin_bottom: f32,
+    pub margin_inner: f32,
+    pub margin_outer: f32,
+    pub font_size: f32,
+    pub line_spacing: f32,
+    pub page_numbers: bool,
+    pub running_headers: bool,
+}
+
+impl PdfOptions {
+    pub fn text_width(&self) -> f32 {
+        self.page_width - self.margin_inner - self.margin_outer
+    }
+
+    pub fn text_height(&self) -> f32 {
+        self.page_height - self.margin_top - self.margin_bottom
+    }
+
+    // As margens devem deixar espaço suficiente para o texto
+    pub fn has_room_for_text(&self) -> bool {
+        self.text_width() >= MIN_TEXT_WIDTH && self.text_height() >= MIN_TEXT_HEIGHT
+    }
+
+    fn line_height(&self) -> f32 {
+        self.font_size * self.line_spacing * PT_TO_MM
+    }
+}
+
+// Arquivos TrueType embutidos no PDF
+pub struct PdfFonts {
+    pub regular: Vec<u8>,
+    pub bold: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum PdfError {
+    Font(String),
+    Render(printpdf::Error),
+}
+
+impl std::fmt::Display for PdfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PdfError::Font(e) => write!(f, "fonte inválida: {}", e),
+            PdfError::Render(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<printpdf::Error> for PdfError {
+    fn from(e: printpdf::Error) -> Self {
+        PdfError::Render(e)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Regular,
+    Bold,
+}
+
+// Largura de texto usada na paginação
+trait TextWidth {
+    // Largura do texto em mm no corpo informado (pt)
+    fn width(&self, text: &str, style: Style, size: f32) -> f32;
+}
+
+// Medidas de largura de texto a partir das fontes
+struct Metrics<'a> {
+    regular: Face<'a>,
+    bold: Face<'a>,
+}
+
+impl<'a> Metrics<'a> {
+    fn new(fonts: &'a PdfFonts) -> Result<Self, PdfError> {
+        Ok(Self {
+            regular: Face::parse(&fonts.regular, 0).map_err(|e| PdfError::Font(e.to_string()))?,
+            bold: Face::parse(&fonts.bold, 0).map_err(|e| PdfError::Font(e.to_string()))?,
+        })
+    }
+}
+
+impl TextWidth for Metrics<'_> {
+    fn width(&self, text: &str, style: Style, size: f32) -> f32 {
+        let face = match style {
+            Style::Regular => &self.regular,
+            Style::Bold => &self.bold,
+        };
+        let units: u32 = text
+            .chars()
+            .map(|c| {
+                face.glyph_index(c)
+                    .and_then(|glyph| face.glyph_hor_advance(glyph))
+                    .unwrap_or(0) as u32
+            })
+            .sum();
+        units as f32 / face.units_per_em() as f32 * size * PT_TO_MM
+    }
+}
+
+// Trecho de texto posicionado na página, relativo ao canto superior
+// esquerdo da mancha de texto (y cresce para baixo, na linha de base)
+struct Placed {
+    x: f32,
+    y: f32,
+    text: String,
+    style: Style,
+    size: f32,
+}
+
+#[derive(Debug, PartialEq)]
+enum PageKind {
+    Title,
+    Blank,
+    ChapterStart,
+    Body,
+}
+
+struct Page {
+    kind: PageKind,
+    items: Vec<Placed>,
+}
+
+// Paginação do livro antes da renderização
+struct Layout<'a> {
+    options: &'a PdfOptions,
+    metrics: &'a dyn TextWidth,
+    pages: Vec<Page>,
+    // Posição da próxima linha de base na página atual
+    cursor: f32,
+    // Capítulos e a página (índice) onde começam, para os marcadores
+    bookmarks: Vec<(String, usize)>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(options: &'a PdfOptions, metrics: &'a dyn TextWidth) -> Self {
+        Self { options, metrics, pages: Vec::new(), cursor: 0.0, bookmarks: Vec::new() }
+    }
+
+    fn new_page(&mut self, kind: PageKind) {
+        self.pages.push(Page { kind, items: Vec::new() });
+        self.cursor = 0.0;
+    }
+
+    fn place(&mut self, x: f32, text: String, style: Style, size: f32) {
+        let y = self.cursor;
+        if let Some(page) = self.pages.last_mut() {
+            page.items.push(Placed { x, y, text, style, size });
+        }
+    }
+
+    // Avançar uma linha, abrindo nova página quando a atual acabar
+    fn advance(&mut self, height: f32) {
+        if self.cursor + height > self.options.text_height() {
+            self.new_page(PageKind::Body);
+        }
+        self.cursor += height;
+    }
+
+    // Quebrar um texto em linhas que caibam na largura disponível.
+    // Cada linha é a lista de palavras; `indent` vale só para a primeira.
+    fn wrap(&self, text: &str, style: Style, size: f32, indent: f32) -> Vec<Vec<String>> {
+        let width = self.options.text_width();
+        let space = self.metrics.width(" ", style, size);
+        let mut lines: Vec<Vec<String>> = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut current_width = 0.0;
+
+        for word in text.split_whitespace() {
+            let mut word = word.to_string();
+            loop {
+                let line_start = if lines.is_empty() { indent } else { 0.0 };
+                let available = width - line_start;
+                let word_width = self.metrics.width(&word, style, size);
+                let needed = if current.is_empty() { line_start + word_width } else { current_width + space + word_width };
+
+                if needed <= width {
+                    current_width = needed;
+                    current.push(word);
+                    break;
+                }
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                    continue;
+                }
+                // Palavra maior que a linha inteira: cortar onde couber
+                let mut cut = 0;
+                for (i, c) in word.char_indices() {
+                    if self.metrics.width(&word[..i + c.len_utf8()], style, size) > available {
+                        break;
+                    }
+                    cut = i + c.len_utf8();
+                }
+                let cut = if cut == 0 { word.chars().next().map_or(word.len(), char::len_utf8) } else { cut };
+                let rest = word.split_off(cut);
+                lines.push(vec![word]);
+                if rest.is_empty() {
+                    break;
+                }
+                word = rest;
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        lines
+    }
+
+    // Capítulo: começa sempre em página ímpar, com o título mais abaixo.
+    // O Markdown entra como texto, sem a marcação.
+    fn chapter(&mut self, chapter: &Chapter, format: ContentFormat) {
+        if self.pages.len() % 2 == 1 {
+            self.new_page(PageKind::Blank);
+        }
+        self.new_page(PageKind::ChapterStart);
+        self.bookmarks.push((chapter.title.clone(), self.pages.len() - 1));
+
+        let heading_size = self.options.font_size * 1.6;
+        let heading_height = heading_size * 1.3 * PT_TO_MM;
+        self.cursor = self.options.text_height() / 4.0;
+        for line in self.wrap(&chapter.title, Style::Bold, heading_size, 0.0) {
+            let text = line.join(" ");
+            let x = (self.options.text_width() - self.metrics.width(&text, Style::Bold, heading_size)) / 2.0;
+            self.advance(heading_height);
+            self.place(x, text, Style::Bold, heading_size);
+        }
+        self.cursor += self.options.line_height() * 2.0;
+
+        let content = render_plain_text(&chapter.content, format);
+        for (i, paragraph) in paragraphs(&content).iter().enumerate() {
+            // Primeiro parágrafo do capítulo sem recuo
+            let indent = if i == 0 { 0.0 } else { self.options.font_size * 1.5 * PT_TO_MM };
+            for (j, line) in paragraph.iter().enumerate() {
+                self.paragraph_line(line, if j == 0 { indent } else { 0.0 });
+            }
+        }
+    }
+
+    // Uma linha do texto original, justificada exceto na última linha
+    fn paragraph_line(&mut self, text: &str, indent: f32) {
+        let size = self.options.font_size;
+        let width = self.options.text_width();
+        let line_height = self.options.line_height();
+        let lines = self.wrap(text, Style::Regular, size, indent);
+        let count = lines.len();
+
+        for (i, words) in lines.into_iter().enumerate() {
+            self.advance(line_height);
+            let start = if i == 0 { indent } else { 0.0 };
+            let last = i + 1 == count;
+
+            if last || words.len() < 2 {
+                self.place(start, words.join(" "), Style::Regular, size);
+                continue;
+            }
+
+            let words_width: f32 = words.iter().map(|w| self.metrics.width(w, Style::Regular, size)).sum();
+            let gap = (width - start - words_width) / (words.len() - 1) as f32;
+            let mut x = start;
+            for word in words {
+                let word_width = self.metrics.width(&word, Style::Regular, size);
+                self.place(x, word, Style::Regular, size);
+                x += word_width + gap;
+            }
+        }
+    }
+
+    fn title_page(&mut self, book: &BookWithCategory) {
+        self.new_page(PageKind::Title);
+        let width = self.options.text_width();
+        let title_size = self.options.font_size * 2.2;
+        let author_size = self.options.font_size * 1.3;
+
+        self.cursor = self.options.text_height() / 3.0;
+        for line in self.wrap(&book.title, Style::Bold, title_size, 0.0) {
+            let text = line.join(" ");
+            let x = (width - self.metrics.width(&text, Style::Bold, title_size)) / 2.0;
+            self.cursor += title_size * 1.3 * PT_TO_MM;
+            self.place(x, text, Style::Bold, title_size);
+        }
+        self.cursor += self.options.line_height() * 2.0;
+        for line in self.wrap(&book.author, Style::Regular, author_size, 0.0) {
+            let text = line.join(" ");
+            let x = (width - self.metrics.width(&text, Style::Regular, author_size)) / 2.0;
+            self.cursor += author_size * 1.3 * PT_TO_MM;
+            self.place(x, text, Style::Regular, author_size);
+        }
+    }
+}
+
+// Margem esquerda da página: a interna nas ímpares (recto), a externa nas pares
+fn left_margin(number: usize, options: &PdfOptions) -> f32 {
+    if number % 2 == 1 { options.margin_inner } else { options.margin_outer }
+}
+
+// Cabeçalho corrente e número da página, em corpo menor e centralizados na
+// mancha de texto. Posições (x, y) em mm a partir do canto inferior esquerdo.
+fn running_elements(
+    page: &Page,
+    number: usize,
+    book: &BookWithCategory,
+    options: &PdfOptions,
+    metrics: &dyn TextWidth,
+) -> Vec<(f32, f32, String)> {
+    let mut elements = Vec::new();
+    if matches!(page.kind, PageKind::Title | PageKind::Blank) {
+        return elements;
+    }
+
+    let left = left_margin(number, options);
+    let small = options.font_size * 0.8;
+    let centered = |text: &str| left + (options.text_width() - metrics.width(text, Style::Regular, small)) / 2.0;
+
+    // Cabeçalho corrente: autor nas páginas pares, título nas ímpares
+    if options.running_headers && page.kind == PageKind::Body {
+        let header = if number % 2 == 1 { &book.title } else { &book.author };
+        elements.push((centered(header), options.page_height - options.margin_top / 2.0, header.clone()));
+    }
+
+    if options.page_numbers {
+        let folio = number.to_string();
+        elements.push((centered(&folio), options.margin_bottom / 2.0, folio));
+    }
+    elements
+}
+
+fn render_page(
+    layer: &PdfLayerReference,
+    page: &Page,
+    number: usize,
+    book: &BookWithCategory,
+    options: &PdfOptions,
+    metrics: &dyn TextWidth,
+    fonts: (&IndirectFontRef, &IndirectFontRef),
+) {
+    let left = left_margin(number, options);
+    let top = options.page_height - options.margin_top;
+    let font = |style: Style| if style == Style::Bold { fonts.1 } else { fonts.0 };
+
+    for item in &page.items {
+        layer.use_text(item.text.clone(), item.size, Mm(left + item.x), Mm(top - item.y), font(item.style));
+    }
+
+    let small = options.font_size * 0.8;
+    for (x, y, text) in running_elements(page, number, book, options, metrics) {
+        layer.use_text(text, small, Mm(x), Mm(y), fonts.0);
+    }
+}
+
+// Folha de rosto e capítulos distribuídos em páginas. Um livro sem
+// capítulos vira um único capítulo com o conteúdo completo.
+fn paginate<'a>(
+    book: &BookWithCategory,
+    chapters: &[Chapter],
+    options: &'a PdfOptions,
+    metrics: &'a dyn TextWidth,
+) -> Layout<'a> {
+    let mut layout = Layout::new(options, metrics);
+    layout.title_page(book);
+    if chapters.is_empty() {
+        let whole_book = Chapter {
+            id: book.id,
+            book_id: book.id,
+            position: 1,
+            title: book.title.clone(),
+            content: book.content.clone(),
+            created_at: book.created_at,
+            updated_at: book.updated_at,
+        };
+        layout.chapter(&whole_book, book.content_format);
+    }
+    for chapter in chapters {
+        layout.chapter(chapter, book.content_format);
+    }
+    layout
+}
+
+// Gerar o PDF: folha de rosto, e cada capítulo começando em página ímpar
+pub fn build_pdf(
+    book: &BookWithCategory,
+    chapters: &[Chapter],
+    options: &PdfOptions,
+    fonts: &PdfFonts,
+) -> Result<Vec<u8>, PdfError> {
+    let metrics = Metrics::new(fonts)?;
+    let layout = paginate(book, chapters, options, &metrics);
+
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(book.title.as_str(), Mm(options.page_width), Mm(options.page_height), "Texto");
+    let doc: PdfDocumentReference = doc.with_author(book.author.as_str()).with_subject(book.cat_name.as_str());
+    let regular = doc.add_external_font(fonts.regular.as_slice())?;
+    let bold = doc.add_external_font(fonts.bold.as_slice())?;
+
+    let mut page_indices = Vec::with_capacity(layout.pages.len());
+    for (i, page) in layout.pages.iter().enumerate() {
+        let (page_index, layer_index) = if i == 0 {
+            (first_page, first_layer)
+        } else {
+            doc.add_page(Mm(options.page_width), Mm(options.page_height), "Texto")
+        };
+        let layer = doc.get_page(page_index).get_layer(layer_index);
+        render_page(&layer, page, i + 1, book, options, &metrics, (&regular, &bold));
+        page_indices.push(page_index);
+    }
+
+    for (title, page) in &layout.bookmarks {
+        doc.add_bookmark(title.as_str(), page_indices[*page]);
+    }
+
+    Ok(doc.save_to_bytes()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{test_book, test_chapter};
+
+    // Fonte de largura fixa: 1 mm por caractere, em qualquer corpo
+    struct Monospace;
+
+    impl TextWidth for Monospace {
+        fn width(&self, text: &str, _style: Style, _size: f32) -> f32 {
+            text.chars().count() as f32
+        }
+    }
+
+    // Mancha de texto de 50 x 100 mm
+    fn options() -> PdfOptions {
+        PdfOptions {
+            page_width: 70.0,
+            page_height: 120.0,
+            margin_top: 10.0,
+            margin_bottom: 10.0,
+            margin_inner: 12.0,
+            margin_outer: 8.0,
+            font_size: 10.0,
+            line_spacing: 1.2,
+            page_numbers: true,
+            running_headers: true,
+        }
+    }
+
+    fn line_lengths(lines: &[Vec<String>]) -> Vec<usize> {
+        lines.iter().map(|words| words.join(" ").chars().count()).collect()
+    }
+
+    #[test]
+    fn wrap_fills_lines_up_to_the_text_width() {
+        let options = options();
+        let layout = Layout::new(&options, &Monospace);
+        let text = ["palavra01"; 12].join(" ");
+
+        let lines = layout.wrap(&text, Style::Regular, 10.0, 0.0);
+        assert_eq!(lines.iter().map(Vec::len).collect::<Vec<_>>(), vec![5, 5, 2]);
+        assert_eq!(line_lengths(&lines), vec![49, 49, 19]);
+    }
+
+    #[test]
+    fn wrap_indents_only_the_first_line() {
+        let options = options();
+        let layout = Layout::new(&options, &Monospace);
+        let text = ["palavra01"; 12].join(" ");
+
+        let lines = layout.wrap(&text, Style::Regular, 10.0, 10.0);
+        assert_eq!(lines.iter().map(Vec::len).collect::<Vec<_>>(), vec![4, 5, 3]);
+    }
+
+    #[test]
+    fn wrap_cuts_words_longer_than_the_line() {
+        let options = options();
+        let layout = Layout::new(&options, &Monospace);
+
+        let lines = layout.wrap(&format!("curta {}", "x".repeat(120)), Style::Regular, 10.0, 0.0);
+        assert_eq!(line_lengths(&lines), vec![5, 50, 50, 20]);
+        assert!(layout.wrap("  \n ", Style::Regular, 10.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn chapters_start_on_odd_pages() {
+        let options = options();
+        let book = test_book(ContentFormat::Plain);
+        // 30 linhas: a primeira página do capítulo comporta 13, o restante vai para a seguinte
+        let long = ["linha"; 30].join("\n");
+        let chapters = [test_chapter(1, "Um", "Curto."), test_chapter(2, "Dois", &long), test_chapter(3, "Três", "Fim.")];
+
+        let layout = paginate(&book, &chapters, &options, &Monospace);
+        let kinds: Vec<&PageKind> = layout.pages.iter().map(|page| &page.kind).collect();
+        use PageKind::*;
+        assert_eq!(kinds, vec![&Title, &Blank, &ChapterStart, &Blank, &ChapterStart, &Body, &ChapterStart]);
+
+        let starts: Vec<usize> = layout.bookmarks.iter().map(|(_, index)| index + 1).collect();
+        assert_eq!(starts, vec![3, 5, 7]);
+        assert!(layout.pages.iter().filter(|page| page.kind == Blank).all(|page| page.items.is_empty()));
+    }
+
+    #[test]
+    fn text_stays_inside_the_text_block() {
+        let options = options();
+        let book = test_book(ContentFormat::Plain);
+        let paragraph = ["palavra"; 40].join(" ");
+        let content = [paragraph.as_str(); 8].join("\n\n");
+
+        let layout = paginate(&book, &[test_chapter(1, "Um", &content)], &options, &Monospace);
+        assert!(layout.pages.len() > 4);
+        for page in &layout.pages {
+            for item in &page.items {
+                assert!(item.y > 0.0 && item.y <= options.text_height(), "y = {}", item.y);
+                assert!(item.x >= 0.0 && item.x + Monospace.width(&item.text, item.style, item.size) <= options.text_width() + 0.001);
+            }
+        }
+        // Páginas de continuação começam na primeira linha da mancha
+        let first = &layout.pages.iter().find(|page| page.kind == PageKind::Body).unwrap().items[0];
+        assert!((first.y - options.line_height()).abs() < 0.001);
+    }
+
+    #[test]
+    fn running_header_alternates_and_page_number_is_centered() {
+        let options = options();
+        let book = test_book(ContentFormat::Plain);
+        let body = Page { kind: PageKind::Body, items: Vec::new() };
+
+        // Verso (par): autor, margem externa à esquerda
+        assert_eq!(
+            running_elements(&body, 6, &book, &options, &Monospace),
+            vec![(8.0 + (50.0 - 3.0) / 2.0, 115.0, "Ana".to_string()), (8.0 + (50.0 - 1.0) / 2.0, 5.0, "6".to_string())]
+        );
+        // Recto (ímpar): título, margem interna à esquerda
+        assert_eq!(
+            running_elements(&body, 7, &book, &options, &Monospace),
+            vec![(12.0 + (50.0 - 13.0) / 2.0, 115.0, book.title.clone()), (12.0 + (50.0 - 1.0) / 2.0, 5.0, "7".to_string())]
+        );
+    }
+
+    #[test]
+    fn chapter_openings_have_no_header_and_title_and_blank_pages_have_no_folio() {
+        let mut options = options();
+        let book = test_book(ContentFormat::Plain);
+        let page = |kind| Page { kind, items: Vec::new() };
+
+        let opening = running_elements(&page(PageKind::ChapterStart), 3, &book, &options, &Monospace);
+        assert_eq!(opening.iter().map(|(_, _, text)| text.as_str()).collect::<Vec<_>>(), vec!["3"]);
+        assert!(running_elements(&page(PageKind::Title), 1, &book, &options, &Monospace).is_empty());
+        assert!(running_elements(&page(PageKind::Blank), 2, &book, &options, &Monospace).is_empty());
+
+        options.page_numbers = false;
+        options.running_headers = false;
+        assert!(running_elements(&page(PageKind::Body), 4, &book, &options, &Monospace).is_empty());
+    }
+}
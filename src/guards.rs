@@ -0,0 +1,229 @@
+use rocket::request::{FromRequest, Outcome, Request};
+use uuid::Uuid;
+use crate::{
+    models::{User, JwtConfig, Role, TokenScope},
+    database::get_pool,
+    errors::{guard_error, AppError},
+    tokens,
+};
+
+// Usuário autenticado a partir do header `Authorization: Bearer <token>`,
+// com um JWT de sessão ou um token de acesso pessoal
+pub struct AuthUser(pub User);
+
+// Usuário autenticado por login (JWT). Operações da conta, como trocar a senha
+// ou criar tokens, não aceitam tokens de acesso pessoal.
+pub struct SessionUser(pub User);
+
+// Usuário autenticado com papel de administrador
+pub struct AdminUser(pub User);
+
+// Usuário autenticado que pode criar livros (autor ou administrador)
+pub struct AuthorUser(pub User);
+
+impl AuthUser {
+    // Administradores podem gerenciar recursos de outros usuários
+    pub fn is_admin(&self) -> bool {
+        self.0.role == Role::Admin
+    }
+
+    // Verifica se o usuário pode modificar um recurso pertencente a `owner_id`
+    pub fn can_manage(&self, owner_id: Uuid) -> bool {
+        self.0.id == owner_id || self.is_admin()
+    }
+}
+
+// Forma de autenticação da requisição, guardada pelo guard AuthUser
+#[derive(Debug, Clone, Default)]
+enum Credential {
+    #[default]
+    Session,
+    AccessToken(Vec<TokenScope>),
+}
+
+impl Credential {
+    fn grants(&self, required: TokenScope) -> bool {
+        match self {
+            Credential::Session => true,
+            Credential::AccessToken(scopes) => scopes.iter().any(|scope| scope.grants(required)),
+        }
+    }
+}
+
+// Permissão que um token de acesso pessoal precisa ter em cada rota (pelo
+// nome do handler). Rotas fora da lista, como as operações da própria conta e
+// o registro do progresso de leitura, exigem login com email e senha.
+fn access_token_scope(route: &str) -> Option<TokenScope> {
+    match route {
+        "verify_token" | "get_me" | "get_my_books" | "search_books" | "get_book" | "get_book_html"
+        | "export_epub" | "export_pdf" | "get_chapters" | "get_chapter" | "get_revisions"
+        | "diff_revisions" | "get_revision" | "get_progress" | "continue_reading" | "get_my_stats"
+        | "get_user_stats" => Some(TokenScope::BooksRead),
+        "create_book" | "import_book" | "update_book" | "update_book_status" | "delete_book"
+        | "create_chapter" | "update_chapter" | "reorder_chapters" | "delete_chapter"
+        | "restore_revision" => Some(TokenScope::BooksWrite),
+        "get_users" | "get_lockout_events" | "unlock_user" | "get_user" | "create_user" | "update_user"
+        | "update_user_role" | "delete_user" | "create_category" => Some(TokenScope::Admin),
+        _ => None,
+    }
+}
+
+fn session_required() -> AppError {
+    AppError::forbidden(
+        "SESSION_REQUIRED",
+        "Esta operação exige login com email e senha; tokens de acesso pessoal não são aceitos",
+    )
+}
+
+fn insufficient_scope(required: TokenScope) -> AppError {
+    AppError::forbidden(
+        "INSUFFICIENT_SCOPE",
+        format!("O token de acesso não tem a permissão {}", required.as_str()),
+    )
+}
+
+// Extrair o token do header Authorization
+fn bearer_token<'r>(request: &'r Request<'_>) -> Option<&'r str> {
+    request
+        .headers()
+        .get_one("Authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = match bearer_token(request) {
+            Some(token) => token,
+            None => return Outcome::Error(guard_error(request, AppError::unauthorized("MISSING_TOKEN", "Token de acesso ausente"))),
+        };
+
+        let invalid_token = || AppError::unauthorized("INVALID_TOKEN", "Token de acesso inválido ou expirado");
+
+        let (user_id, credential) = if token.starts_with(tokens::ACCESS_TOKEN_PREFIX) {
+            // Token de acesso pessoal: a permissão exigida depende da rota
+            let (user_id, scopes) = match tokens::authenticate_access_token(token).await {
+                Ok(row) => row,
+                Err(e) => return Outcome::Error(guard_error(request, e)),
+            };
+            let credential = Credential::AccessToken(scopes);
+            let route = request.route().and_then(|route| route.name.as_deref()).unwrap_or_default();
+            match access_token_scope(route) {
+                Some(required) if credential.grants(required) => {}
+                Some(required) => return Outcome::Error(guard_error(request, insufficient_scope(required))),
+                None => return Outcome::Error(guard_error(request, session_required())),
+            }
+            (user_id, credential)
+        } else {
+            // Validar assinatura e expiração do token
+            let jwt_config = JwtConfig::new();
+            let claims = match jwt_config.validate_token(token) {
+                Ok(claims) => claims,
+                Err(_) => return Outcome::Error(guard_error(request, invalid_token())),
+            };
+
+            match Uuid::parse_str(&claims.sub) {
+                Ok(id) => (id, Credential::Session),
+                Err(_) => return Outcome::Error(guard_error(request, invalid_token())),
+            }
+        };
+        request.local_cache(|| credential);
+
+        // Carregar usuário do banco (o token pode pertencer a um usuário removido)
+        match sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(get_pool())
+            .await
+        {
+            Ok(user) => Outcome::Success(AuthUser(user)),
+            Err(sqlx::Error::RowNotFound) => {
+                Outcome::Error(guard_error(request, AppError::unauthorized("INVALID_TOKEN", "Usuário do token não existe mais")))
+            }
+            Err(e) => Outcome::Error(guard_error(request, AppError::internal("Erro ao carregar usuário autenticado", e))),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.guard::<AuthUser>().await {
+            Outcome::Success(_) if !request.local_cache(Credential::default).grants(TokenScope::Admin) => {
+                Outcome::Error(guard_error(request, insufficient_scope(TokenScope::Admin)))
+            }
+            Outcome::Success(auth) if auth.is_admin() => Outcome::Success(AdminUser(auth.0)),
+            Outcome::Success(_) => {
+                Outcome::Error(guard_error(request, AppError::forbidden("ADMIN_REQUIRED", "Apenas administradores podem acessar este recurso")))
+            }
+            Outcome::Error(e) => Outcome::Error(e),
+            Outcome::Forward(status) => Outcome::Forward(status),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthorUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.guard::<AuthUser>().await {
+            Outcome::Success(auth) if auth.0.role.can_write_books() => Outcome::Success(AuthorUser(auth.0)),
+            Outcome::Success(_) => {
+                Outcome::Error(guard_error(request, AppError::forbidden("AUTHOR_REQUIRED", "Apenas autores podem criar livros")))
+            }
+            Outcome::Error(e) => Outcome::Error(e),
+            Outcome::Forward(status) => Outcome::Forward(status),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SessionUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.guard::<AuthUser>().await {
+            Outcome::Success(_) if matches!(request.local_cache(Credential::default), Credential::AccessToken(_)) => {
+                Outcome::Error(guard_error(request, session_required()))
+            }
+            Outcome::Success(auth) => Outcome::Success(SessionUser(auth.0)),
+            Outcome::Error(e) => Outcome::Error(e),
+            Outcome::Forward(status) => Outcome::Forward(status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_token_scope_follows_the_route() {
+        assert_eq!(access_token_scope("verify_token"), Some(TokenScope::BooksRead));
+        assert_eq!(access_token_scope("get_book"), Some(TokenScope::BooksRead));
+        assert_eq!(access_token_scope("update_chapter"), Some(TokenScope::BooksWrite));
+        assert_eq!(access_token_scope("delete_user"), Some(TokenScope::Admin));
+    }
+
+    #[test]
+    fn account_and_reading_state_routes_require_session() {
+        for route in ["update_progress", "update_me", "change_password", "create_access_token", "logout_all", ""] {
+            assert_eq!(access_token_scope(route), None, "{}", route);
+        }
+    }
+
+    #[test]
+    fn credential_grants_by_scope() {
+        assert!(Credential::Session.grants(TokenScope::Admin));
+        let read = Credential::AccessToken(vec![TokenScope::BooksRead]);
+        assert!(read.grants(TokenScope::BooksRead));
+        assert!(!read.grants(TokenScope::BooksWrite));
+        assert!(!Credential::AccessToken(Vec::new()).grants(TokenScope::BooksRead));
+    }
+}
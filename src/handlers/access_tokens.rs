@@ -0,0 +1,101 @@
+use rocket::{delete, get, post, serde::json::Json};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+use crate::{
+    models::{AccessToken, ApiResponse, CreateAccessTokenRequest, CreatedAccessTokenResponse, Role, TokenScope},
+    database::get_pool,
+    validation::Valid,
+    errors::{ApiResult, AppError},
+    guards::SessionUser,
+    tokens,
+};
+
+// Caracteres do token guardados em claro (prefixo e início do valor aleatório)
+const TOKEN_PREFIX_LEN: usize = 12;
+
+// Listar os tokens de acesso pessoal do usuário (sem os valores)
+#[get("/me/tokens")]
+pub async fn get_access_tokens(auth: SessionUser) -> ApiResult<Vec<AccessToken>> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, AccessToken>(
+        r#"
+        SELECT id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+        FROM personal_access_tokens
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#
+    )
+    .bind(auth.0.id)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(access_tokens) => Ok(Json(ApiResponse::success(access_tokens, "Tokens de acesso listados com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar tokens de acesso", e)),
+    }
+}
+
+// Criar um token de acesso pessoal. O valor é exibido apenas nesta resposta.
+#[post("/me/tokens", data = "<token_data>")]
+pub async fn create_access_token(auth: SessionUser, token_data: Valid<CreateAccessTokenRequest>) -> ApiResult<CreatedAccessTokenResponse> {
+    let pool = get_pool();
+    let request = token_data.into_inner();
+
+    // A permissão admin só vale para administradores
+    if request.scopes.contains(&TokenScope::Admin) && auth.0.role != Role::Admin {
+        return Err(AppError::forbidden("ADMIN_REQUIRED", "Apenas administradores podem criar tokens com a permissão admin"));
+    }
+
+    let mut scopes = request.scopes;
+    scopes.sort_by_key(|scope| scope.as_str());
+    scopes.dedup();
+
+    let token = tokens::generate_access_token();
+    let expires_at = request.expires_in_days.map(|days| Utc::now() + Duration::days(days));
+
+    match sqlx::query_as::<_, AccessToken>(
+        r#"
+        INSERT INTO personal_access_tokens (user_id, name, token_hash, token_prefix, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+        "#
+    )
+    .bind(auth.0.id)
+    .bind(request.name.trim())
+    .bind(tokens::hash_token(&token))
+    .bind(&token[..TOKEN_PREFIX_LEN])
+    .bind(&scopes)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(access_token) => Ok(Json(ApiResponse::success(
+            CreatedAccessTokenResponse { token, access_token },
+            "Token de acesso criado. Copie o valor agora; ele não será exibido novamente",
+        ))),
+        Err(e) => Err(AppError::internal("Erro ao criar token de acesso", e)),
+    }
+}
+
+// Revogar um token de acesso pessoal do usuário
+#[delete("/me/tokens/<id>")]
+pub async fn revoke_access_token(auth: SessionUser, id: String) -> ApiResult<()> {
+    let pool = get_pool();
+    let token_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    match sqlx::query("DELETE FROM personal_access_tokens WHERE id = $1 AND user_id = $2")
+        .bind(token_id)
+        .bind(auth.0.id)
+        .execute(pool)
+        .await
+    {
+        Ok(result) if result.rows_affected() > 0 => {
+            Ok(Json(ApiResponse::success((), "Token de acesso revogado com sucesso")))
+        }
+        Ok(_) => Err(AppError::not_found("ACCESS_TOKEN_NOT_FOUND", "Token de acesso não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao revogar token de acesso", e)),
+    }
+}
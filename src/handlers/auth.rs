@@ -1,15 +1,99 @@
-use rocket::{post, http::Status, serde::json::Json};
+use std::net::IpAddr;
+use rocket::{post, serde::json::Json};
 use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
 use crate::{
-    models::{LoginRequest, RegisterRequest, LoginResponse, User, UserResponse, ApiResponse, JwtConfig},
+    models::{
+        LoginRequest, RegisterRequest, LoginResponse, LoginOutcome, MfaChallengeResponse, MfaLoginRequest, RefreshRequest,
+        ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, User, UserResponse, ApiResponse, JwtConfig,
+    },
+    config::settings,
     database::get_pool,
+    validation::Valid,
+    errors::{is_unique_violation, ApiResult, AppError},
+    guards::{AuthUser, SessionUser},
+    handlers::two_factor::verify_second_factor,
+    mailer::{app_link, get_mailer, Email},
+    login_throttle,
+    two_factor::MFA_MAX_ATTEMPTS,
+    tokens,
 };
 
-// Endpoint de login
+// Gerar token de acesso JWT para o usuário
+fn access_token(user: &User) -> Result<String, AppError> {
+    JwtConfig::new()
+        .generate_token(user.id, &user.email)
+        .map_err(|e| AppError::internal("Erro ao gerar token JWT", e))
+}
+
+// Envios recentes de um tipo de email: quantos na última hora, segundos desde
+// o último e segundos até o mais antigo da última hora deixar de contar
+pub(crate) type SendStats = (i64, Option<f64>, Option<f64>);
+
+// Consulta de `SendStats` nos registros de `table` com `column = $1`
+pub(crate) fn send_stats_query(table: &str, column: &str) -> String {
+    format!(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE created_at > NOW() - INTERVAL '1 hour'),
+               EXTRACT(EPOCH FROM NOW() - MAX(created_at))::float8,
+               EXTRACT(EPOCH FROM MIN(created_at) FILTER (WHERE created_at > NOW() - INTERVAL '1 hour')
+                   + INTERVAL '1 hour' - NOW())::float8
+        FROM {}
+        WHERE {} = $1
+        "#,
+        table, column
+    )
+}
+
+// Intervalo mínimo entre envios e limite por hora, usados no reenvio da
+// verificação de email e nos pedidos de redefinição de senha
+pub(crate) fn check_send_limit(code: &'static str, stats: SendStats) -> Result<(), AppError> {
+    let security = &settings().security;
+    let (sent_last_hour, seconds_since_last, seconds_until_slot) = stats;
+
+    if let Some(elapsed) = seconds_since_last {
+        let wait = security.verification_resend_seconds - elapsed as i64;
+        if wait > 0 {
+            return Err(AppError::too_many_requests(
+                code,
+                format!("Aguarde {} segundos para pedir um novo email", wait),
+                wait,
+            ));
+        }
+    }
+    if sent_last_hour >= security.verification_resend_per_hour {
+        return Err(AppError::too_many_requests(
+            code,
+            "Limite de emails por hora atingido; tente mais tarde",
+            seconds_until_slot.map_or(3600, |seconds| seconds.ceil() as i64),
+        ));
+    }
+    Ok(())
+}
+
+// Emitir token de acesso e refresh token para o usuário
+async fn create_session(user: User) -> Result<LoginResponse, AppError> {
+    let token = access_token(&user)?;
+    let refresh_token = tokens::issue_refresh_token(user.id).await?;
+
+    Ok(LoginResponse {
+        token,
+        refresh_token,
+        user: UserResponse::from(user),
+    })
+}
+
+// Endpoint de login. Contas com 2FA recebem um token de segunda etapa em
+// vez da sessão, a ser trocado em /login/mfa junto com o código.
+// Falhas seguidas impõem espera crescente e, no limite, bloqueiam a conta.
 #[post("/login", data = "<login_data>")]
-pub async fn login(login_data: Json<LoginRequest>) -> Result<Json<ApiResponse<LoginResponse>>, Status> {
+pub async fn login(login_data: Json<LoginRequest>, ip: Option<IpAddr>) -> ApiResult<LoginOutcome> {
     let pool = get_pool();
     let login = &login_data.into_inner();
+    let invalid_credentials = || AppError::unauthorized("INVALID_CREDENTIALS", "Email ou senha incorretos");
+
+    login_throttle::check(&login.email, ip).await?;
 
     // Buscar usuário por email
     match sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
@@ -20,43 +104,107 @@ pub async fn login(login_data: Json<LoginRequest>) -> Result<Json<ApiResponse<Lo
         Ok(user) => {
             // Verificar senha
             match verify(&login.password, &user.password_hash) {
+                Ok(valid) if valid && user.has_two_factor() => {
+                    let (mfa_token, expires_at) = tokens::issue_mfa_challenge(user.id).await?;
+                    let challenge = MfaChallengeResponse { mfa_required: true, mfa_token, expires_at };
+                    Ok(Json(ApiResponse::success(
+                        LoginOutcome::MfaRequired(challenge),
+                        "Informe o código de autenticação em dois fatores",
+                    )))
+                }
                 Ok(valid) if valid => {
-                    // Gerar token JWT
-                    let jwt_config = JwtConfig::new();
-                    match jwt_config.generate_token(user.id, &user.email) {
-                        Ok(token) => {
-                            let login_response = LoginResponse {
-                                token,
-                                user: UserResponse::from(user),
-                            };
-                            Ok(Json(ApiResponse::success(login_response, "Login realizado com sucesso")))
-                        }
-                        Err(_) => Err(Status::InternalServerError)
+                    login_throttle::clear(&login.email).await?;
+                    let login_response = create_session(user).await?;
+                    Ok(Json(ApiResponse::success(LoginOutcome::Session(login_response), "Login realizado com sucesso")))
+                }
+                // Hash inválido (ex.: conta sem senha definida) também é uma credencial incorreta
+                result => {
+                    if let Err(e) = result {
+                        log_warn!("Hash de senha inválido para o usuário {}: {}", user.id, e);
                     }
+                    login_throttle::record_failure(&login.email, ip, Some(user.id)).await?;
+                    Err(invalid_credentials())
                 }
-                Ok(_) => Err(Status::Unauthorized),
-                Err(_) => Err(Status::InternalServerError)
             }
         }
-        Err(sqlx::Error::RowNotFound) => Err(Status::Unauthorized),
-        Err(e) => {
-            eprintln!("Erro ao buscar usuário: {}", e);
-            Err(Status::InternalServerError)
+        Err(sqlx::Error::RowNotFound) => {
+            login_throttle::record_failure(&login.email, ip, None).await?;
+            Err(invalid_credentials())
         }
+        Err(e) => Err(AppError::internal("Erro ao buscar usuário", e)),
+    }
+}
+
+// Segunda etapa do login: troca o token da primeira etapa e um código
+// (do aplicativo ou de recuperação) pela sessão. Cada token aceita poucas
+// tentativas; esgotadas, é preciso entrar com a senha de novo. Códigos
+// errados também contam para o bloqueio da conta.
+#[post("/login/mfa", data = "<mfa_data>")]
+pub async fn login_mfa(mfa_data: Valid<MfaLoginRequest>, ip: Option<IpAddr>) -> ApiResult<LoginResponse> {
+    let pool = get_pool();
+    let request = mfa_data.into_inner();
+    let invalid_token = || AppError::unauthorized(
+        "INVALID_MFA_TOKEN",
+        "Etapa de login inválida ou expirada; entre novamente com email e senha",
+    );
+
+    // Reservar uma tentativa de forma atômica antes de conferir o código
+    let (challenge_id, user) = match sqlx::query_as::<_, (Uuid, Uuid)>(
+        r#"
+        UPDATE mfa_challenges SET attempts = attempts + 1
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW() AND attempts < $2
+        RETURNING id, user_id
+        "#
+    )
+    .bind(tokens::hash_token(&request.mfa_token))
+    .bind(MFA_MAX_ATTEMPTS)
+    .fetch_one(pool)
+    .await
+    {
+        Ok((challenge_id, user_id)) => {
+            match sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_one(pool)
+                .await
+            {
+                Ok(user) => (challenge_id, user),
+                Err(e) => return Err(AppError::internal("Erro ao buscar usuário", e)),
+            }
+        }
+        Err(sqlx::Error::RowNotFound) => return Err(invalid_token()),
+        Err(e) => return Err(AppError::internal("Erro ao buscar etapa de login", e)),
+    };
+
+    login_throttle::check(&user.email, ip).await?;
+    if !verify_second_factor(&user, &request.code).await? {
+        login_throttle::record_failure(&user.email, ip, Some(user.id)).await?;
+        return Err(AppError::unauthorized("INVALID_MFA_CODE", "Código de autenticação inválido"));
+    }
+
+    match sqlx::query("UPDATE mfa_challenges SET used_at = NOW() WHERE id = $1 AND used_at IS NULL")
+        .bind(challenge_id)
+        .execute(pool)
+        .await
+    {
+        Ok(result) if result.rows_affected() == 1 => {
+            login_throttle::clear(&user.email).await?;
+            let login_response = create_session(user).await?;
+            Ok(Json(ApiResponse::success(login_response, "Login realizado com sucesso")))
+        }
+        Ok(_) => Err(invalid_token()),
+        Err(e) => Err(AppError::internal("Erro ao concluir login", e)),
     }
 }
 
 // Endpoint de registro
 #[post("/register", data = "<register_data>")]
-pub async fn register(register_data: Json<RegisterRequest>) -> Result<Json<ApiResponse<LoginResponse>>, Status> {
+pub async fn register(register_data: Valid<RegisterRequest>) -> ApiResult<LoginResponse> {
     let pool = get_pool();
     let register = &register_data.into_inner();
 
     // Hash da senha
-    let password_hash = match hash(&register.password, DEFAULT_COST) {
-        Ok(hash) => hash,
-        Err(_) => return Err(Status::InternalServerError),
-    };
+    let password_hash = hash(&register.password, DEFAULT_COST)
+        .map_err(|e| AppError::internal("Erro ao gerar hash da senha", e))?;
 
     // Criar usuário
     match sqlx::query_as::<_, User>(
@@ -65,38 +213,276 @@ pub async fn register(register_data: Json<RegisterRequest>) -> Result<Json<ApiRe
     .bind(&register.name)
     .bind(&register.email)
     .bind(&password_hash)
-    .bind(&register.age)
+    .bind(register.age)
     .fetch_one(pool)
     .await
     {
         Ok(user) => {
-            // Gerar token JWT
-            let jwt_config = JwtConfig::new();
-            match jwt_config.generate_token(user.id, &user.email) {
-                Ok(token) => {
-                    let login_response = LoginResponse {
-                        token,
-                        user: UserResponse::from(user),
-                    };
-                    Ok(Json(ApiResponse::success(login_response, "Usuário registrado com sucesso")))
-                }
-                Err(_) => Err(Status::InternalServerError)
-            }
-        }
-        Err(sqlx::Error::Database(db_err)) if db_err.constraint().is_some() => {
-            Err(Status::Conflict) // Email duplicado
+            // A conta já pode entrar, mas só publica depois de confirmar o email
+            send_verification_email(&user).await?;
+            let login_response = create_session(user).await?;
+            Ok(Json(ApiResponse::success(login_response, "Usuário registrado com sucesso")))
         }
-        Err(e) => {
-            eprintln!("Erro ao criar usuário: {}", e);
-            Err(Status::InternalServerError)
+        Err(e) if is_unique_violation(&e) => {
+            Err(AppError::conflict("EMAIL_TAKEN", "Este email já está cadastrado"))
         }
+        Err(e) => Err(AppError::internal("Erro ao criar usuário", e)),
     }
 }
 
+// Renovar sessão: troca o refresh token por um novo par de tokens
+#[post("/refresh", data = "<refresh_data>")]
+pub async fn refresh(refresh_data: Json<RefreshRequest>) -> ApiResult<LoginResponse> {
+    let request = refresh_data.into_inner();
+    let (user, refresh_token) = tokens::rotate_refresh_token(&request.refresh_token).await?;
+
+    let login_response = LoginResponse {
+        token: access_token(&user)?,
+        refresh_token,
+        user: UserResponse::from(user),
+    };
+    Ok(Json(ApiResponse::success(login_response, "Sessão renovada com sucesso")))
+}
+
+// Encerrar a sessão atual revogando o refresh token
+#[post("/logout", data = "<refresh_data>")]
+pub async fn logout(refresh_data: Json<RefreshRequest>) -> ApiResult<()> {
+    tokens::revoke_refresh_token(&refresh_data.refresh_token).await?;
+    Ok(Json(ApiResponse::success((), "Logout realizado com sucesso")))
+}
+
+// Encerrar todas as sessões do usuário autenticado
+#[post("/logout-all")]
+pub async fn logout_all(auth: SessionUser) -> ApiResult<u64> {
+    let revoked = tokens::revoke_all_for_user(auth.0.id).await?;
+    Ok(Json(ApiResponse::success(revoked, "Todas as sessões foram encerradas")))
+}
+
 // Endpoint para verificar token (protegido)
 #[post("/verify-token")]
-pub async fn verify_token() -> Result<Json<ApiResponse<UserResponse>>, Status> {
-    // Este endpoint seria usado para verificar se o token ainda é válido
-    // A implementação completa incluiria middleware de autenticação
-    Err(Status::NotImplemented)
+pub async fn verify_token(auth: AuthUser) -> ApiResult<UserResponse> {
+    // O guard já validou o token e carregou o usuário
+    Ok(Json(ApiResponse::success(UserResponse::from(auth.0), "Token válido")))
+}
+
+// Email com o link de redefinição de senha
+fn password_reset_email(user: &User, token: &str, minutes: i64) -> Email {
+    Email {
+        to_name: user.name.clone(),
+        to_address: user.email.clone(),
+        subject: "Redefinição de senha".to_string(),
+        body: format!(
+            "Olá, {}!\n\n\
+             Recebemos um pedido para redefinir a senha da sua conta. Para escolher uma nova senha, acesse:\n\n\
+             {}\n\n\
+             O link vale por {} minutos e só pode ser usado uma vez. Se você não fez o pedido, ignore este email.\n",
+            user.name,
+            app_link(&format!("/reset-password?token={}", token)),
+            minutes,
+        ),
+    }
+}
+
+// Pedir o link de redefinição de senha. A resposta é a mesma para emails
+// cadastrados ou não, para não revelar quais contas existem.
+#[post("/forgot-password", data = "<forgot_data>")]
+pub async fn forgot_password(forgot_data: Valid<ForgotPasswordRequest>, ip: Option<IpAddr>) -> ApiResult<()> {
+    let pool = get_pool();
+    let request = forgot_data.into_inner();
+    let sent = || Ok(Json(ApiResponse::success((), "Se o email estiver cadastrado, enviaremos um link para redefinir a senha")));
+
+    // Mesmo limite do reenvio da verificação, por email e por IP (no IP só o
+    // limite por hora, já que vários usuários podem compartilhar o endereço).
+    // Pedidos para emails sem cadastro também contam, para não revelar quais existem.
+    let email = request.email.trim().to_lowercase();
+    let ip_address = ip.map(|ip| ip.to_string());
+    let keys = [("email", Some(email.clone())), ("ip_address", ip_address.clone())];
+    for (column, value) in keys {
+        let Some(value) = value else { continue };
+        match sqlx::query_as::<_, SendStats>(&send_stats_query("password_reset_requests", column))
+            .bind(value)
+            .fetch_one(pool)
+            .await
+        {
+            Ok((sent_last_hour, _, seconds_until_slot)) if column == "ip_address" => {
+                check_send_limit("PASSWORD_RESET_THROTTLED", (sent_last_hour, None, seconds_until_slot))?
+            }
+            Ok(stats) => check_send_limit("PASSWORD_RESET_THROTTLED", stats)?,
+            Err(e) => return Err(AppError::internal("Erro ao verificar pedidos anteriores", e)),
+        }
+    }
+
+    if let Err(e) = sqlx::query("INSERT INTO password_reset_requests (email, ip_address) VALUES ($1, $2)")
+        .bind(&email)
+        .bind(&ip_address)
+        .execute(pool)
+        .await
+    {
+        return Err(AppError::internal("Erro ao registrar pedido de redefinição de senha", e));
+    }
+
+    // A busca da conta, o novo token e o envio do email seguem em segundo plano,
+    // para a resposta levar o mesmo tempo com emails cadastrados ou não
+    tokio::spawn(send_password_reset(email, request.email.trim().to_string()));
+    sent()
+}
+
+// Criar o token de redefinição e enviar o link, se o email tiver conta. A busca
+// ignora maiúsculas, como o limite de pedidos; havendo mais de uma conta, vale a
+// grafia exata informada.
+async fn send_password_reset(email: String, typed_email: String) {
+    let pool = get_pool();
+    let user = match sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE LOWER(email) = $1 ORDER BY email = $2 DESC LIMIT 1"
+    )
+    .bind(&email)
+    .bind(&typed_email)
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return,
+        Err(e) => {
+            log_error!("Erro ao buscar usuário para redefinição de senha: {}", e);
+            return;
+        }
+    };
+
+    // Um novo pedido invalida os links anteriores ainda não usados
+    let minutes = settings().security.password_reset_minutes;
+    let token = tokens::generate_opaque_token();
+    if let Err(e) = sqlx::query(
+        r#"
+        WITH invalidated AS (
+            DELETE FROM password_reset_tokens WHERE user_id = $1 AND used_at IS NULL
+        )
+        INSERT INTO password_reset_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(user.id)
+    .bind(tokens::hash_token(&token))
+    .bind(Utc::now() + Duration::minutes(minutes))
+    .execute(pool)
+    .await
+    {
+        log_error!("Erro ao criar token de redefinição de senha: {}", e);
+        return;
+    }
+
+    if let Err(e) = get_mailer().send(&password_reset_email(&user, &token, minutes)).await {
+        log_error!("Falha ao enviar email de redefinição de senha: {}", e);
+    }
+}
+
+// Redefinir a senha com o token recebido por email. O token é consumido
+// na mesma transação da troca de senha, as sessões abertas e os tokens de
+// acesso pessoal são revogados e um bloqueio da conta por excesso de
+// tentativas deixa de valer.
+#[post("/reset-password", data = "<reset_data>")]
+pub async fn reset_password(reset_data: Valid<ResetPasswordRequest>) -> ApiResult<()> {
+    let pool = get_pool();
+    let request = reset_data.into_inner();
+
+    let password_hash = hash(&request.new_password, DEFAULT_COST)
+        .map_err(|e| AppError::internal("Erro ao gerar hash da senha", e))?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<Uuid, sqlx::Error> = async {
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE password_reset_tokens SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+            "#
+        )
+        .bind(tokens::hash_token(&request.token))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let email = sqlx::query_scalar::<_, String>(
+            "UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2 RETURNING email"
+        )
+        .bind(&password_hash)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tokens::revoke_credentials(&mut tx, user_id).await?;
+        login_throttle::unlock_after_reset(&mut tx, &email, user_id).await?;
+
+        Ok(user_id)
+    }
+    .await;
+
+    match result {
+        Ok(_) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar redefinição de senha", e))?;
+            Ok(Json(ApiResponse::success((), "Senha redefinida com sucesso")))
+        }
+        Err(sqlx::Error::RowNotFound) => Err(AppError::bad_request(
+            "INVALID_RESET_TOKEN",
+            "Link de redefinição de senha inválido, expirado ou já utilizado",
+        )),
+        Err(e) => Err(AppError::internal("Erro ao redefinir senha", e)),
+    }
+}
+
+// Enviar o link de verificação para o email atual do usuário. Falhas de
+// envio ficam no log; o usuário pode pedir o reenvio.
+pub(crate) async fn send_verification_email(user: &User) -> Result<(), AppError> {
+    let token = tokens::issue_email_verification_token(user).await?;
+    let email = Email {
+        to_name: user.name.clone(),
+        to_address: user.email.clone(),
+        subject: "Confirme seu email".to_string(),
+        body: format!(
+            "Olá, {}!\n\n\
+             Para confirmar seu email e poder publicar livros, acesse:\n\n\
+             {}\n\n\
+             O link vale por {} horas. Se você não criou uma conta, ignore este email.\n",
+            user.name,
+            app_link(&format!("/verify-email?token={}", token)),
+            settings().security.email_verification_hours,
+        ),
+    };
+
+    if let Err(e) = get_mailer().send(&email).await {
+        log_error!("Falha ao enviar email de verificação: {}", e);
+    }
+    Ok(())
+}
+
+// Confirmar o email com o token recebido. O token só vale para o email
+// para o qual foi enviado.
+#[post("/verify-email", data = "<verify_data>")]
+pub async fn verify_email(verify_data: Valid<VerifyEmailRequest>) -> ApiResult<UserResponse> {
+    let pool = get_pool();
+    let request = verify_data.into_inner();
+
+    match sqlx::query_as::<_, User>(
+        r#"
+        WITH used AS (
+            UPDATE email_verification_tokens t SET used_at = NOW()
+            FROM users u
+            WHERE t.token_hash = $1 AND t.used_at IS NULL AND t.expires_at > NOW()
+              AND u.id = t.user_id AND u.email = t.email
+            RETURNING t.user_id
+        )
+        UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW()
+        WHERE id = (SELECT user_id FROM used)
+        RETURNING *
+        "#
+    )
+    .bind(tokens::hash_token(&request.token))
+    .fetch_one(pool)
+    .await
+    {
+        Ok(user) => Ok(Json(ApiResponse::success(UserResponse::from(user), "Email confirmado com sucesso"))),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::bad_request(
+            "INVALID_VERIFICATION_TOKEN",
+            "Link de verificação inválido, expirado ou já utilizado",
+        )),
+        Err(e) => Err(AppError::internal("Erro ao confirmar email", e)),
+    }
 }
@@ -1,17 +1,23 @@
-use rocket::{get, post, put, delete, http::Status, serde::json::Json};
+use chrono::Utc;
+use rocket::{get, post, put, delete, serde::json::Json};
 use uuid::Uuid;
+use validator::Validate;
 use crate::{
     models::{ApiResponse},
     models::book::{
-        BookWithCategory, Category, CreateBookRequest, UpdateBookRequest, 
-        CreateCategoryRequest
+        BookWithCategory, BookStatus, Category, CreateBookRequest, UpdateBookRequest, 
+        UpdateBookStatusRequest, CreateCategoryRequest, BookSearchRequest, BookSearchResponse, BookSearchResult
     },
     database::get_pool,
+    validation::{validation_error, Valid},
+    errors::{is_foreign_key_violation, is_unique_violation, ApiResult, AppError},
+    guards::{AuthUser, AuthorUser, AdminUser},
+    handlers::{chapters::split_book_chapters, revisions::record_revision},
 };
 
-// Listar todos os livros públicos
+// Listar todos os livros públicos e publicados
 #[get("/books")]
-pub async fn get_books() -> Result<Json<ApiResponse<Vec<BookWithCategory>>>, Status> {
+pub async fn get_books() -> ApiResult<Vec<BookWithCategory>> {
     let pool = get_pool();
     
     match sqlx::query_as::<_, BookWithCategory>(
@@ -20,7 +26,7 @@ pub async fn get_books() -> Result<Json<ApiResponse<Vec<BookWithCategory>>>, Sta
                c.created_at as cat_created_at, c.updated_at as cat_updated_at
         FROM books b
         JOIN categories c ON b.category_id = c.id
-        WHERE b.is_public = true
+        WHERE b.is_public = true AND b.status = 'published'
         ORDER BY b.created_at DESC
         "#
     )
@@ -28,98 +34,269 @@ pub async fn get_books() -> Result<Json<ApiResponse<Vec<BookWithCategory>>>, Sta
     .await
     {
         Ok(books) => Ok(Json(ApiResponse::success(books, "Livros listados com sucesso"))),
-        Err(e) => {
-            eprintln!("Erro ao buscar livros: {}", e);
-            Err(Status::InternalServerError)
+        Err(e) => Err(AppError::internal("Erro ao buscar livros", e)),
+    }
+}
+
+// Marcadores usados pelo ts_headline; trocados por <mark> depois de escapar o HTML do trecho
+const HIGHLIGHT_START: char = '\u{E000}';
+const HIGHLIGHT_END: char = '\u{E001}';
+
+// Filtros da busca: texto ($1), categoria ($2), autor ($3), visibilidade ($4, $5) e is_public ($6)
+const SEARCH_FILTER: &str = r#"
+    FROM books b
+    JOIN categories c ON b.category_id = c.id
+    WHERE ($1::text IS NULL OR b.search_vector @@ websearch_to_tsquery('portuguese_unaccent', $1))
+      AND ($2::uuid IS NULL OR b.category_id = $2)
+      AND ($3::text IS NULL OR unaccent(b.author) ILIKE '%' || unaccent($3) || '%')
+      AND ((b.is_public = true AND b.status = 'published') OR b.user_id = $4 OR $5)
+      AND ($6::boolean IS NULL OR b.is_public = $6)
+"#;
+
+// Escapar o HTML do trecho e destacar os termos encontrados
+fn highlight_snippet(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            HIGHLIGHT_START => html.push_str("<mark>"),
+            HIGHLIGHT_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
         }
     }
+    html
 }
 
-// Buscar livro por ID
-#[get("/books/<id>")]
-pub async fn get_book(id: String) -> Result<Json<ApiResponse<BookWithCategory>>, Status> {
+// Busca textual de livros (português, sem acentos) com relevância, trechos e paginação
+#[get("/books/search?<params..>")]
+pub async fn search_books(auth: Option<AuthUser>, params: BookSearchRequest) -> ApiResult<BookSearchResponse> {
     let pool = get_pool();
-    let book_id = match Uuid::parse_str(&id) {
-        Ok(id) => id,
-        Err(_) => return Err(Status::BadRequest),
+    params.validate().map_err(|e| validation_error(&e))?;
+
+    let query = params.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let author = params.author.as_deref().map(str::trim).filter(|a| !a.is_empty());
+    let category_id = match params.category_id.as_deref().filter(|c| !c.is_empty()) {
+        Some(id) => match Uuid::parse_str(id) {
+            Ok(id) => Some(id),
+            Err(_) => return Err(AppError::invalid_id()),
+        },
+        None => None,
     };
+    let page = params.page.unwrap_or(1);
+    let per_page = params.per_page.unwrap_or(20);
+    let user_id = auth.as_ref().map(|a| a.0.id);
+    let is_admin = auth.as_ref().is_some_and(|a| a.is_admin());
 
-    match sqlx::query_as::<_, BookWithCategory>(
+    let total = match sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", SEARCH_FILTER))
+        .bind(query)
+        .bind(category_id)
+        .bind(author)
+        .bind(user_id)
+        .bind(is_admin)
+        .bind(params.is_public)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(total) => total,
+        Err(e) => return Err(AppError::internal("Erro ao contar resultados da busca", e)),
+    };
+
+    let headline_options = format!(
+        "StartSel={}, StopSel={}, MaxWords=35, MinWords=15, MaxFragments=2, FragmentDelimiter=\" … \"",
+        HIGHLIGHT_START, HIGHLIGHT_END
+    );
+
+    let sql = format!(
         r#"
-        SELECT b.*, c.id as cat_id, c.name as cat_name, c.description as cat_description, 
-               c.created_at as cat_created_at, c.updated_at as cat_updated_at
-        FROM books b
-        JOIN categories c ON b.category_id = c.id
-        WHERE b.id = $1 AND b.is_public = true
-        "#
+        SELECT b.*, c.id as cat_id, c.name as cat_name, c.description as cat_description,
+               c.created_at as cat_created_at, c.updated_at as cat_updated_at,
+               CASE WHEN $1::text IS NULL THEN 0
+                    ELSE ts_rank_cd(b.search_vector, websearch_to_tsquery('portuguese_unaccent', $1))
+               END::real AS rank,
+               CASE WHEN $1::text IS NULL THEN NULL
+                    ELSE ts_headline('portuguese_unaccent', coalesce(b.description, '') || ' ' || b.content,
+                                     websearch_to_tsquery('portuguese_unaccent', $1), $7)
+               END AS snippet
+        {}
+        ORDER BY rank DESC, b.created_at DESC
+        LIMIT $8 OFFSET $9
+        "#,
+        SEARCH_FILTER
+    );
+
+    match sqlx::query_as::<_, BookSearchResult>(&sql)
+        .bind(query)
+        .bind(category_id)
+        .bind(author)
+        .bind(user_id)
+        .bind(is_admin)
+        .bind(params.is_public)
+        .bind(&headline_options)
+        .bind(i64::from(per_page))
+        .bind(i64::from(page - 1) * i64::from(per_page))
+        .fetch_all(pool)
+        .await
+    {
+        Ok(mut books) => {
+            for result in &mut books {
+                result.snippet = result.snippet.as_deref().map(highlight_snippet);
+            }
+            let response = BookSearchResponse { books, total, page, per_page };
+            Ok(Json(ApiResponse::success(response, "Busca realizada com sucesso")))
+        }
+        Err(e) => Err(AppError::internal("Erro ao buscar livros", e)),
+    }
+}
+
+// Verificar se o usuário autenticado pode modificar o livro
+pub(crate) async fn check_book_permission(book_id: Uuid, auth: &AuthUser) -> Result<(), AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_scalar::<_, Uuid>("SELECT user_id FROM books WHERE id = $1")
+        .bind(book_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(owner_id) if auth.can_manage(owner_id) => Ok(()),
+        Ok(_) => Err(AppError::forbidden("NOT_BOOK_OWNER", "Apenas o autor do livro pode modificá-lo")),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::not_found("BOOK_NOT_FOUND", "Livro não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao verificar dono do livro", e)),
+    }
+}
+
+// Verificar se o livro existe e é visível para o usuário (público e publicado, próprio ou admin)
+pub(crate) async fn check_book_visible(book_id: Uuid, auth: Option<&AuthUser>) -> Result<(), AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM books WHERE id = $1 AND ((is_public = true AND status = 'published') OR user_id = $2 OR $3))"
     )
     .bind(book_id)
+    .bind(auth.map(|a| a.0.id))
+    .bind(auth.is_some_and(|a| a.is_admin()))
     .fetch_one(pool)
     .await
     {
-        Ok(book) => Ok(Json(ApiResponse::success(book, "Livro encontrado"))),
-        Err(sqlx::Error::RowNotFound) => Err(Status::NotFound),
-        Err(e) => {
-            eprintln!("Erro ao buscar livro: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Ok(true) => Ok(()),
+        Ok(false) => Err(AppError::not_found("BOOK_NOT_FOUND", "Livro não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao verificar livro", e)),
     }
 }
 
-// Criar novo livro
-#[post("/books", data = "<book_data>")]
-pub async fn create_book(book_data: Json<CreateBookRequest>) -> Result<Json<ApiResponse<BookWithCategory>>, Status> {
+// Buscar livro com categoria, se visível para o usuário (livros privados ou
+// não publicados só são visíveis para o dono e administradores)
+pub(crate) async fn fetch_visible_book(book_id: Uuid, auth: Option<&AuthUser>) -> Result<BookWithCategory, AppError> {
     let pool = get_pool();
-    let book = &book_data.into_inner();
-
-    // TODO: Implementar autenticação para obter user_id do token
-    // Por enquanto, vamos usar um user_id fixo para demonstração
-    let user_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
 
     match sqlx::query_as::<_, BookWithCategory>(
         r#"
-        WITH new_book AS (
-            INSERT INTO books (title, author, isbn, description, content, category_id, user_id, is_public)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING *
-        )
         SELECT b.*, c.id as cat_id, c.name as cat_name, c.description as cat_description, 
                c.created_at as cat_created_at, c.updated_at as cat_updated_at
-        FROM new_book b
+        FROM books b
         JOIN categories c ON b.category_id = c.id
+        WHERE b.id = $1 AND ((b.is_public = true AND b.status = 'published') OR b.user_id = $2 OR $3)
         "#
     )
-    .bind(&book.title)
-    .bind(&book.author)
-    .bind(&book.isbn)
-    .bind(&book.description)
-    .bind(&book.content)
-    .bind(&book.category_id)
-    .bind(user_id)
-    .bind(book.is_public)
+    .bind(book_id)
+    .bind(auth.map(|a| a.0.id))
+    .bind(auth.is_some_and(|a| a.is_admin()))
     .fetch_one(pool)
     .await
     {
-        Ok(new_book) => Ok(Json(ApiResponse::success(new_book, "Livro criado com sucesso"))),
-        Err(sqlx::Error::Database(db_err)) if db_err.constraint().is_some() => {
-            Err(Status::Conflict) // Categoria não existe
+        Ok(book) => Ok(book),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::not_found("BOOK_NOT_FOUND", "Livro não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao buscar livro", e)),
+    }
+}
+
+// Buscar livro por ID
+#[get("/books/<id>")]
+pub async fn get_book(auth: Option<AuthUser>, id: String) -> ApiResult<BookWithCategory> {
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let book = fetch_visible_book(book_id, auth.as_ref()).await?;
+    Ok(Json(ApiResponse::success(book, "Livro encontrado")))
+}
+
+// Inserir livro do usuário, dividindo o conteúdo em capítulos e registrando a primeira revisão
+pub(crate) async fn insert_book(book: &CreateBookRequest, user_id: Uuid) -> Result<BookWithCategory, AppError> {
+    let pool = get_pool();
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<BookWithCategory, sqlx::Error> = async {
+        let new_book = sqlx::query_as::<_, BookWithCategory>(
+            r#"
+            WITH new_book AS (
+                INSERT INTO books (title, author, isbn, description, content, content_format, category_id, user_id, is_public)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                RETURNING *
+            )
+            SELECT b.*, c.id as cat_id, c.name as cat_name, c.description as cat_description, 
+                   c.created_at as cat_created_at, c.updated_at as cat_updated_at
+            FROM new_book b
+            JOIN categories c ON b.category_id = c.id
+            "#
+        )
+        .bind(&book.title)
+        .bind(&book.author)
+        .bind(&book.isbn)
+        .bind(&book.description)
+        .bind(&book.content)
+        .bind(book.content_format)
+        .bind(book.category_id)
+        .bind(user_id)
+        .bind(book.is_public)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        split_book_chapters(&mut *tx, new_book.id).await?;
+        record_revision(&mut tx, new_book.id, user_id).await?;
+        Ok(new_book)
+    }
+    .await;
+
+    match result {
+        Ok(new_book) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar criação do livro", e))?;
+            Ok(new_book)
         }
-        Err(e) => {
-            eprintln!("Erro ao criar livro: {}", e);
-            Err(Status::InternalServerError)
+        Err(e) if is_foreign_key_violation(&e) => {
+            Err(AppError::unprocessable("CATEGORY_NOT_FOUND", "A categoria informada não existe"))
         }
+        Err(e) => Err(AppError::internal("Erro ao criar livro", e)),
     }
 }
 
+// Criar novo livro
+#[post("/books", data = "<book_data>")]
+pub async fn create_book(author: AuthorUser, book_data: Valid<CreateBookRequest>) -> ApiResult<BookWithCategory> {
+    let book = book_data.into_inner();
+
+    // O livro pertence ao usuário autenticado
+    let new_book = insert_book(&book, author.0.id).await?;
+    Ok(Json(ApiResponse::success(new_book, "Livro criado com sucesso")))
+}
+
 // Atualizar livro
 #[put("/books/<id>", data = "<book_data>")]
-pub async fn update_book(id: String, book_data: Json<UpdateBookRequest>) -> Result<Json<ApiResponse<BookWithCategory>>, Status> {
+pub async fn update_book(auth: AuthUser, id: String, book_data: Valid<UpdateBookRequest>) -> ApiResult<BookWithCategory> {
     let pool = get_pool();
     let book_id = match Uuid::parse_str(&id) {
         Ok(id) => id,
-        Err(_) => return Err(Status::BadRequest),
+        Err(_) => return Err(AppError::invalid_id()),
     };
 
+    // Apenas o dono do livro ou um administrador pode editá-lo
+    check_book_permission(book_id, &auth).await?;
+
     let book = &book_data.into_inner();
 
     // Construir query dinamicamente
@@ -151,6 +328,10 @@ pub async fn update_book(id: String, book_data: Json<UpdateBookRequest>) -> Resu
         params.push(format!("content = ${}", param_count));
         param_count += 1;
     }
+    if let Some(_content_format) = book.content_format {
+        params.push(format!("content_format = ${}", param_count));
+        param_count += 1;
+    }
     if let Some(_category_id) = book.category_id {
         params.push(format!("category_id = ${}", param_count));
         param_count += 1;
@@ -161,10 +342,10 @@ pub async fn update_book(id: String, book_data: Json<UpdateBookRequest>) -> Resu
     }
 
     if params.is_empty() {
-        return Err(Status::BadRequest);
+        return Err(AppError::bad_request("NO_FIELDS", "Nenhum campo para atualizar"));
     }
 
-    params.push(format!("updated_at = NOW()"));
+    params.push("updated_at = NOW()".to_string());
     query.push_str(&params.join(", "));
     query.push_str(&format!(" WHERE id = ${} RETURNING *", param_count));
     query.push_str(r#"
@@ -192,6 +373,9 @@ pub async fn update_book(id: String, book_data: Json<UpdateBookRequest>) -> Resu
     if let Some(ref _content) = book.content {
         query_builder = query_builder.bind(_content);
     }
+    if let Some(_content_format) = book.content_format {
+        query_builder = query_builder.bind(_content_format);
+    }
     if let Some(_category_id) = book.category_id {
         query_builder = query_builder.bind(_category_id);
     }
@@ -200,28 +384,136 @@ pub async fn update_book(id: String, book_data: Json<UpdateBookRequest>) -> Resu
     }
     query_builder = query_builder.bind(book_id);
 
-    match query_builder.fetch_one(pool).await {
-        Ok(updated_book) => Ok(Json(ApiResponse::success(updated_book, "Livro atualizado com sucesso"))),
-        Err(sqlx::Error::RowNotFound) => Err(Status::NotFound),
-        Err(sqlx::Error::Database(db_err)) if db_err.constraint().is_some() => {
-            Err(Status::Conflict) // Categoria não existe
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<BookWithCategory, sqlx::Error> = async {
+        let updated_book = query_builder.fetch_one(&mut *tx).await?;
+
+        // Conteúdo completo substituído: sincronizar os capítulos
+        if book.content.is_some() {
+            split_book_chapters(&mut *tx, book_id).await?;
+            record_revision(&mut tx, book_id, auth.0.id).await?;
+        }
+        Ok(updated_book)
+    }
+    .await;
+
+    match result {
+        Ok(updated_book) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar atualização do livro", e))?;
+            Ok(Json(ApiResponse::success(updated_book, "Livro atualizado com sucesso")))
         }
-        Err(e) => {
-            eprintln!("Erro ao atualizar livro: {}", e);
-            Err(Status::InternalServerError)
+        Err(sqlx::Error::RowNotFound) => Err(AppError::not_found("BOOK_NOT_FOUND", "Livro não encontrado")),
+        Err(e) if is_foreign_key_violation(&e) => {
+            Err(AppError::unprocessable("CATEGORY_NOT_FOUND", "A categoria informada não existe"))
         }
+        Err(e) => Err(AppError::internal("Erro ao atualizar livro", e)),
+    }
+}
+
+// Alterar o status editorial do livro (dono ou administradores).
+// Publicar com `publish_at` no futuro agenda a publicação em vez de aplicá-la.
+#[put("/books/<id>/status", data = "<status_data>")]
+pub async fn update_book_status(auth: AuthUser, id: String, status_data: Valid<UpdateBookStatusRequest>) -> ApiResult<BookWithCategory> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+    let request = status_data.into_inner();
+
+    check_book_permission(book_id, &auth).await?;
+
+    // Publicar (ou agendar) exige email confirmado
+    if request.status == BookStatus::Published && !auth.0.is_email_verified() {
+        return Err(AppError::forbidden(
+            "EMAIL_NOT_VERIFIED",
+            "Confirme seu email antes de publicar livros",
+        ));
+    }
+
+    let current = match sqlx::query_scalar::<_, BookStatus>("SELECT status FROM books WHERE id = $1")
+        .bind(book_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(status) => status,
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::not_found("BOOK_NOT_FOUND", "Livro não encontrado")),
+        Err(e) => return Err(AppError::internal("Erro ao buscar status do livro", e)),
+    };
+
+    if request.publish_at.is_some() && request.status != BookStatus::Published {
+        return Err(AppError::bad_request(
+            "INVALID_PUBLISH_AT",
+            "A data de publicação só pode ser informada ao publicar o livro",
+        ));
+    }
+
+    if !current.can_transition_to(request.status) {
+        return Err(AppError::conflict(
+            "INVALID_STATUS_TRANSITION",
+            format!("Não é possível mudar o status de '{}' para '{}'", current.as_str(), request.status.as_str()),
+        ));
+    }
+
+    let scheduled = request.publish_at.filter(|at| *at > Utc::now());
+
+    // Agendamento: o status continua o mesmo e o livro é publicado pela tarefa em segundo plano.
+    // O UPDATE só vale se o status ainda for o verificado acima: outra requisição (ou a
+    // publicação agendada) pode tê-lo mudado entre a consulta e a escrita.
+    let update = if scheduled.is_some() {
+        "UPDATE books SET scheduled_publish_at = $2, updated_at = NOW() WHERE id = $1 AND status = $4 RETURNING *"
+    } else {
+        r#"
+            UPDATE books SET
+                status = $3,
+                published_at = CASE WHEN $3 = 'published' THEN NOW() ELSE published_at END,
+                scheduled_publish_at = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND status = $4
+            RETURNING *
+        "#
+    };
+
+    match sqlx::query_as::<_, BookWithCategory>(&format!(
+        r#"
+        WITH updated_book AS ({})
+        SELECT b.*, c.id as cat_id, c.name as cat_name, c.description as cat_description, 
+               c.created_at as cat_created_at, c.updated_at as cat_updated_at
+        FROM updated_book b
+        JOIN categories c ON b.category_id = c.id
+        "#,
+        update
+    ))
+    .bind(book_id)
+    .bind(scheduled)
+    .bind(request.status)
+    .bind(current)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(book) if scheduled.is_some() => Ok(Json(ApiResponse::success(book, "Publicação agendada com sucesso"))),
+        Ok(book) => Ok(Json(ApiResponse::success(book, "Status do livro atualizado com sucesso"))),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::conflict(
+            "INVALID_STATUS_TRANSITION",
+            "O status do livro mudou durante a atualização; tente novamente",
+        )),
+        Err(e) => Err(AppError::internal("Erro ao atualizar status do livro", e)),
     }
 }
 
 // Deletar livro
 #[delete("/books/<id>")]
-pub async fn delete_book(id: String) -> Result<Json<ApiResponse<()>>, Status> {
+pub async fn delete_book(auth: AuthUser, id: String) -> ApiResult<()> {
     let pool = get_pool();
     let book_id = match Uuid::parse_str(&id) {
         Ok(id) => id,
-        Err(_) => return Err(Status::BadRequest),
+        Err(_) => return Err(AppError::invalid_id()),
     };
 
+    // Apenas o dono do livro ou um administrador pode deletá-lo
+    check_book_permission(book_id, &auth).await?;
+
     match sqlx::query("DELETE FROM books WHERE id = $1")
         .bind(book_id)
         .execute(pool)
@@ -230,17 +522,14 @@ pub async fn delete_book(id: String) -> Result<Json<ApiResponse<()>>, Status> {
         Ok(result) if result.rows_affected() > 0 => {
             Ok(Json(ApiResponse::success((), "Livro deletado com sucesso")))
         }
-        Ok(_) => Err(Status::NotFound),
-        Err(e) => {
-            eprintln!("Erro ao deletar livro: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Ok(_) => Err(AppError::not_found("BOOK_NOT_FOUND", "Livro não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao deletar livro", e)),
     }
 }
 
 // Listar categorias
 #[get("/categories")]
-pub async fn get_categories() -> Result<Json<ApiResponse<Vec<Category>>>, Status> {
+pub async fn get_categories() -> ApiResult<Vec<Category>> {
     let pool = get_pool();
     
     match sqlx::query_as::<_, Category>("SELECT * FROM categories ORDER BY name")
@@ -248,16 +537,13 @@ pub async fn get_categories() -> Result<Json<ApiResponse<Vec<Category>>>, Status
         .await
     {
         Ok(categories) => Ok(Json(ApiResponse::success(categories, "Categorias listadas com sucesso"))),
-        Err(e) => {
-            eprintln!("Erro ao buscar categorias: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Err(e) => Err(AppError::internal("Erro ao buscar categorias", e)),
     }
 }
 
 // Criar categoria
 #[post("/categories", data = "<category_data>")]
-pub async fn create_category(category_data: Json<CreateCategoryRequest>) -> Result<Json<ApiResponse<Category>>, Status> {
+pub async fn create_category(_admin: AdminUser, category_data: Valid<CreateCategoryRequest>) -> ApiResult<Category> {
     let pool = get_pool();
     let category = &category_data.into_inner();
 
@@ -270,12 +556,9 @@ pub async fn create_category(category_data: Json<CreateCategoryRequest>) -> Resu
     .await
     {
         Ok(new_category) => Ok(Json(ApiResponse::success(new_category, "Categoria criada com sucesso"))),
-        Err(sqlx::Error::Database(db_err)) if db_err.constraint().is_some() => {
-            Err(Status::Conflict) // Nome duplicado
-        }
-        Err(e) => {
-            eprintln!("Erro ao criar categoria: {}", e);
-            Err(Status::InternalServerError)
+        Err(e) if is_unique_violation(&e) => {
+            Err(AppError::conflict("CATEGORY_EXISTS", "Já existe uma categoria com este nome"))
         }
+        Err(e) => Err(AppError::internal("Erro ao criar categoria", e)),
     }
 }
@@ -0,0 +1,331 @@
+use rocket::{get, post, put, delete, serde::json::Json};
+use sqlx::PgConnection;
+use uuid::Uuid;
+use crate::{
+    models::ApiResponse,
+    models::book::{Chapter, CreateChapterRequest, UpdateChapterRequest, ReorderChaptersRequest},
+    database::get_pool,
+    validation::Valid,
+    errors::{ApiResult, AppError},
+    guards::AuthUser,
+    handlers::{books::{check_book_permission, check_book_visible}, revisions::{lock_book, record_revision}},
+};
+
+fn chapter_not_found() -> AppError {
+    AppError::not_found("CHAPTER_NOT_FOUND", "Capítulo não encontrado")
+}
+
+fn parse_ids(book_id: &str, chapter_id: &str) -> Result<(Uuid, Uuid), AppError> {
+    match (Uuid::parse_str(book_id), Uuid::parse_str(chapter_id)) {
+        (Ok(book_id), Ok(chapter_id)) => Ok((book_id, chapter_id)),
+        _ => Err(AppError::invalid_id()),
+    }
+}
+
+// Recriar books.content a partir dos capítulos; o texto completo continua
+// sendo usado pela leitura e pela busca. A introdução (ou o texto de um livro
+// sem títulos) entra como foi escrita, sem "Capítulo N: ..."; fora da
+// primeira posição ela passa a ter título como os demais capítulos.
+async fn sync_book_content(conn: &mut PgConnection, book_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE chapters SET has_heading = TRUE WHERE book_id = $1 AND position > 1 AND NOT has_heading")
+        .bind(book_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE books SET
+            content = COALESCE((
+                SELECT string_agg(
+                    CASE
+                        WHEN has_heading THEN 'Capítulo ' || number || ': ' || title || E'\n\n' || content
+                        ELSE content
+                    END,
+                    E'\n\n' ORDER BY position
+                )
+                FROM (
+                    SELECT position, title, content, has_heading,
+                           COUNT(*) FILTER (WHERE has_heading) OVER (ORDER BY position) AS number
+                    FROM chapters
+                    WHERE book_id = $1
+                ) numbered
+            ), ''),
+            updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(book_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+// Sincronizar os capítulos de um livro com o conteúdo completo (criação, edição e restauração)
+pub(crate) async fn split_book_chapters<'c>(executor: impl sqlx::PgExecutor<'c>, book_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT split_book_chapters($1)")
+        .bind(book_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+// Listar capítulos do livro em ordem
+#[get("/books/<id>/chapters")]
+pub async fn get_chapters(auth: Option<AuthUser>, id: String) -> ApiResult<Vec<Chapter>> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    check_book_visible(book_id, auth.as_ref()).await?;
+
+    match sqlx::query_as::<_, Chapter>("SELECT * FROM chapters WHERE book_id = $1 ORDER BY position")
+        .bind(book_id)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(chapters) => Ok(Json(ApiResponse::success(chapters, "Capítulos listados com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar capítulos", e)),
+    }
+}
+
+// Buscar capítulo por ID
+#[get("/books/<id>/chapters/<chapter_id>")]
+pub async fn get_chapter(auth: Option<AuthUser>, id: String, chapter_id: String) -> ApiResult<Chapter> {
+    let pool = get_pool();
+    let (book_id, chapter_id) = parse_ids(&id, &chapter_id)?;
+
+    check_book_visible(book_id, auth.as_ref()).await?;
+
+    match sqlx::query_as::<_, Chapter>("SELECT * FROM chapters WHERE id = $1 AND book_id = $2")
+        .bind(chapter_id)
+        .bind(book_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(chapter) => Ok(Json(ApiResponse::success(chapter, "Capítulo encontrado"))),
+        Err(sqlx::Error::RowNotFound) => Err(chapter_not_found()),
+        Err(e) => Err(AppError::internal("Erro ao buscar capítulo", e)),
+    }
+}
+
+// Criar capítulo (no final ou na posição informada, deslocando os seguintes)
+#[post("/books/<id>/chapters", data = "<chapter_data>")]
+pub async fn create_chapter(auth: AuthUser, id: String, chapter_data: Valid<CreateChapterRequest>) -> ApiResult<Chapter> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+    let chapter = chapter_data.into_inner();
+
+    check_book_permission(book_id, &auth).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<Chapter, sqlx::Error> = async {
+        // Bloquear o livro para serializar alterações na ordem dos capítulos
+        lock_book(&mut tx, book_id).await?;
+
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM chapters WHERE book_id = $1")
+            .bind(book_id)
+            .fetch_one(&mut *tx)
+            .await? as i32;
+        let position = chapter.position.unwrap_or(count + 1).min(count + 1);
+
+        sqlx::query("UPDATE chapters SET position = position + 1 WHERE book_id = $1 AND position >= $2")
+            .bind(book_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+
+        let created = sqlx::query_as::<_, Chapter>(
+            "INSERT INTO chapters (book_id, position, title, content) VALUES ($1, $2, $3, $4) RETURNING *"
+        )
+        .bind(book_id)
+        .bind(position)
+        .bind(chapter.title.trim())
+        .bind(&chapter.content)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sync_book_content(&mut tx, book_id).await?;
+        record_revision(&mut tx, book_id, auth.0.id).await?;
+        Ok(created)
+    }
+    .await;
+
+    match result {
+        Ok(created) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar criação do capítulo", e))?;
+            Ok(Json(ApiResponse::success(created, "Capítulo criado com sucesso")))
+        }
+        Err(e) => Err(AppError::internal("Erro ao criar capítulo", e)),
+    }
+}
+
+// Atualizar título e/ou conteúdo do capítulo
+#[put("/books/<id>/chapters/<chapter_id>", data = "<chapter_data>", rank = 2)]
+pub async fn update_chapter(auth: AuthUser, id: String, chapter_id: String, chapter_data: Valid<UpdateChapterRequest>) -> ApiResult<Chapter> {
+    let pool = get_pool();
+    let (book_id, chapter_id) = parse_ids(&id, &chapter_id)?;
+    let chapter = chapter_data.into_inner();
+
+    if chapter.title.is_none() && chapter.content.is_none() {
+        return Err(AppError::bad_request("NO_FIELDS", "Nenhum campo para atualizar"));
+    }
+
+    check_book_permission(book_id, &auth).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<Chapter, sqlx::Error> = async {
+        // O livro é bloqueado antes dos capítulos, na mesma ordem dos demais handlers
+        lock_book(&mut tx, book_id).await?;
+
+        let updated = sqlx::query_as::<_, Chapter>(
+            r#"
+            UPDATE chapters SET
+                title = COALESCE($3, title),
+                content = COALESCE($4, content),
+                -- Um título novo na introdução precisa aparecer no texto do livro
+                has_heading = has_heading OR ($3 IS NOT NULL AND $3 IS DISTINCT FROM title),
+                updated_at = NOW()
+            WHERE id = $1 AND book_id = $2
+            RETURNING *
+            "#
+        )
+        .bind(chapter_id)
+        .bind(book_id)
+        .bind(chapter.title.as_deref().map(str::trim))
+        .bind(&chapter.content)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sync_book_content(&mut tx, book_id).await?;
+        record_revision(&mut tx, book_id, auth.0.id).await?;
+        Ok(updated)
+    }
+    .await;
+
+    match result {
+        Ok(updated) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar atualização do capítulo", e))?;
+            Ok(Json(ApiResponse::success(updated, "Capítulo atualizado com sucesso")))
+        }
+        Err(sqlx::Error::RowNotFound) => Err(chapter_not_found()),
+        Err(e) => Err(AppError::internal("Erro ao atualizar capítulo", e)),
+    }
+}
+
+// Reordenar capítulos: a lista deve conter exatamente os capítulos do livro
+#[put("/books/<id>/chapters/order", data = "<order_data>", rank = 1)]
+pub async fn reorder_chapters(auth: AuthUser, id: String, order_data: Valid<ReorderChaptersRequest>) -> ApiResult<Vec<Chapter>> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+    let chapter_ids = order_data.into_inner().chapter_ids;
+
+    check_book_permission(book_id, &auth).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    if let Err(e) = lock_book(&mut tx, book_id).await {
+        return Err(AppError::internal("Erro ao bloquear livro", e));
+    }
+
+    let mut current = match sqlx::query_scalar::<_, Uuid>("SELECT id FROM chapters WHERE book_id = $1 FOR UPDATE")
+        .bind(book_id)
+        .fetch_all(&mut *tx)
+        .await
+    {
+        Ok(ids) => ids,
+        Err(e) => return Err(AppError::internal("Erro ao buscar capítulos", e)),
+    };
+
+    let mut requested = chapter_ids.clone();
+    current.sort();
+    requested.sort();
+    if current != requested {
+        return Err(AppError::unprocessable(
+            "INVALID_CHAPTER_ORDER",
+            "A nova ordem deve conter cada capítulo do livro exatamente uma vez",
+        ));
+    }
+
+    let result: Result<Vec<Chapter>, sqlx::Error> = async {
+        sqlx::query(
+            r#"
+            UPDATE chapters c SET position = o.ord::integer, updated_at = NOW()
+            FROM unnest($2::uuid[]) WITH ORDINALITY AS o(id, ord)
+            WHERE c.id = o.id AND c.book_id = $1
+            "#
+        )
+        .bind(book_id)
+        .bind(&chapter_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        sync_book_content(&mut tx, book_id).await?;
+        record_revision(&mut tx, book_id, auth.0.id).await?;
+
+        sqlx::query_as::<_, Chapter>("SELECT * FROM chapters WHERE book_id = $1 ORDER BY position")
+            .bind(book_id)
+            .fetch_all(&mut *tx)
+            .await
+    }
+    .await;
+
+    match result {
+        Ok(chapters) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar nova ordem dos capítulos", e))?;
+            Ok(Json(ApiResponse::success(chapters, "Capítulos reordenados com sucesso")))
+        }
+        Err(e) => Err(AppError::internal("Erro ao reordenar capítulos", e)),
+    }
+}
+
+// Deletar capítulo e fechar o espaço na numeração
+#[delete("/books/<id>/chapters/<chapter_id>")]
+pub async fn delete_chapter(auth: AuthUser, id: String, chapter_id: String) -> ApiResult<()> {
+    let pool = get_pool();
+    let (book_id, chapter_id) = parse_ids(&id, &chapter_id)?;
+
+    check_book_permission(book_id, &auth).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<(), sqlx::Error> = async {
+        lock_book(&mut tx, book_id).await?;
+
+        let position = sqlx::query_scalar::<_, i32>(
+            "DELETE FROM chapters WHERE id = $1 AND book_id = $2 RETURNING position"
+        )
+        .bind(chapter_id)
+        .bind(book_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE chapters SET position = position - 1 WHERE book_id = $1 AND position > $2")
+            .bind(book_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+
+        sync_book_content(&mut tx, book_id).await?;
+        record_revision(&mut tx, book_id, auth.0.id).await
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar remoção do capítulo", e))?;
+            Ok(Json(ApiResponse::success((), "Capítulo deletado com sucesso")))
+        }
+        Err(sqlx::Error::RowNotFound) => Err(chapter_not_found()),
+        Err(e) => Err(AppError::internal("Erro ao deletar capítulo", e)),
+    }
+}
@@ -0,0 +1,160 @@
+use rocket::{get, serde::json::Json};
+use rocket::http::ContentType;
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    models::ApiResponse,
+    models::book::{Chapter, PdfExportRequest, RenderedBook},
+    config::settings,
+    database::get_pool,
+    validation::validation_error,
+    errors::{ApiResult, AppError},
+    guards::AuthUser,
+    export::{self, epub, pdf::{self, PdfFonts, PdfOptions}, ExportFile},
+    handlers::books::fetch_visible_book,
+    render::render_html,
+};
+
+// Layout padrão do PDF: formato A5 com margem interna maior para a encadernação
+const DEFAULT_TRIM: &str = "a5";
+const DEFAULT_MARGIN_TOP: f32 = 18.0;
+const DEFAULT_MARGIN_BOTTOM: f32 = 20.0;
+const DEFAULT_MARGIN_INNER: f32 = 20.0;
+const DEFAULT_MARGIN_OUTER: f32 = 15.0;
+const DEFAULT_FONT_SIZE: f32 = 11.0;
+const DEFAULT_LINE_SPACING: f32 = 1.4;
+
+async fn fetch_chapters(book_id: Uuid) -> Result<Vec<Chapter>, AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, Chapter>("SELECT * FROM chapters WHERE book_id = $1 ORDER BY position")
+        .bind(book_id)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(chapters) => Ok(chapters),
+        Err(e) => Err(AppError::internal("Erro ao buscar capítulos", e)),
+    }
+}
+
+// Conteúdo do livro em HTML para leitura no navegador, conforme o formato
+// declarado (texto puro ou Markdown), sempre sanitizado
+#[get("/books/<id>/html")]
+pub async fn get_book_html(auth: Option<AuthUser>, id: String) -> ApiResult<RenderedBook> {
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let book = fetch_visible_book(book_id, auth.as_ref()).await?;
+    let chapters = fetch_chapters(book_id).await?;
+
+    let mut html = String::new();
+    for chapter in &chapters {
+        html.push_str(&format!(
+            "<section class=\"chapter\" id=\"capitulo-{}\">\n<h2>{}</h2>\n{}</section>\n",
+            chapter.position,
+            export::escape_xml(&chapter.title),
+            render_html(&chapter.content, book.content_format, &format!("c{}-", chapter.position)),
+        ));
+    }
+
+    let rendered = RenderedBook { book_id, content_format: book.content_format, html };
+    Ok(Json(ApiResponse::success(rendered, "Conteúdo renderizado")))
+}
+
+// Exportar livro em EPUB 3 (mesma visibilidade da leitura do livro)
+#[get("/books/<id>/export.epub")]
+pub async fn export_epub(auth: Option<AuthUser>, id: String) -> Result<ExportFile, AppError> {
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let book = fetch_visible_book(book_id, auth.as_ref()).await?;
+    let chapters = fetch_chapters(book_id).await?;
+
+    match epub::build_epub(&book, &chapters) {
+        Ok(bytes) => Ok(ExportFile {
+            content_type: ContentType::new("application", "epub+zip"),
+            filename: format!("{}.epub", export::file_stem(&book.title)),
+            bytes,
+        }),
+        Err(e) => Err(AppError::internal("Erro ao gerar EPUB", e)),
+    }
+}
+
+// Converter os parâmetros da query string nas opções de layout
+fn pdf_options(params: &PdfExportRequest) -> Result<PdfOptions, AppError> {
+    params.validate().map_err(|e| validation_error(&e))?;
+
+    let (page_width, page_height) = match (params.width, params.height) {
+        (Some(width), Some(height)) => (width, height),
+        (None, None) => pdf::trim_size(params.trim.as_deref().unwrap_or(DEFAULT_TRIM))
+            .unwrap_or((148.0, 210.0)),
+        _ => {
+            return Err(AppError::unprocessable(
+                "INVALID_PAGE_SIZE",
+                "Informe largura e altura juntas para um formato personalizado",
+            ))
+        }
+    };
+
+    let options = PdfOptions {
+        page_width,
+        page_height,
+        margin_top: params.margin_top.or(params.margin).unwrap_or(DEFAULT_MARGIN_TOP),
+        margin_bottom: params.margin_bottom.or(params.margin).unwrap_or(DEFAULT_MARGIN_BOTTOM),
+        margin_inner: params.margin_inner.or(params.margin).unwrap_or(DEFAULT_MARGIN_INNER),
+        margin_outer: params.margin_outer.or(params.margin).unwrap_or(DEFAULT_MARGIN_OUTER),
+        font_size: params.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+        line_spacing: params.line_spacing.unwrap_or(DEFAULT_LINE_SPACING),
+        page_numbers: params.page_numbers.unwrap_or(true),
+        running_headers: params.running_headers.unwrap_or(true),
+    };
+
+    if !options.has_room_for_text() {
+        return Err(AppError::unprocessable(
+            "INVALID_LAYOUT",
+            "As margens não deixam espaço suficiente para o texto nesse formato de página",
+        ));
+    }
+    Ok(options)
+}
+
+async fn load_pdf_fonts() -> Result<PdfFonts, AppError> {
+    let config = &settings().export;
+    let regular = tokio::fs::read(&config.pdf_font)
+        .await
+        .map_err(|e| AppError::internal(&format!("Erro ao carregar fonte {}", config.pdf_font), e))?;
+    let bold = tokio::fs::read(&config.pdf_font_bold)
+        .await
+        .map_err(|e| AppError::internal(&format!("Erro ao carregar fonte {}", config.pdf_font_bold), e))?;
+    Ok(PdfFonts { regular, bold })
+}
+
+// Exportar livro em PDF para impressão (formato, margens, corpo do texto,
+// numeração e cabeçalhos configuráveis pela query string)
+#[get("/books/<id>/export.pdf?<params..>")]
+pub async fn export_pdf(auth: Option<AuthUser>, id: String, params: PdfExportRequest) -> Result<ExportFile, AppError> {
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+    let options = pdf_options(&params)?;
+
+    let book = fetch_visible_book(book_id, auth.as_ref()).await?;
+    let chapters = fetch_chapters(book_id).await?;
+    let fonts = load_pdf_fonts().await?;
+    let filename = format!("{}.pdf", export::file_stem(&book.title));
+
+    // A paginação é custosa para livros longos: fora das threads do servidor
+    let rendered = tokio::task::spawn_blocking(move || pdf::build_pdf(&book, &chapters, &options, &fonts))
+        .await
+        .map_err(|e| AppError::internal("Erro ao gerar PDF", e))?;
+
+    match rendered {
+        Ok(bytes) => Ok(ExportFile { content_type: ContentType::PDF, filename, bytes }),
+        Err(e) => Err(AppError::internal("Erro ao gerar PDF", e)),
+    }
+}
@@ -0,0 +1,140 @@
+use rocket::{post, form::Form, http::Status, serde::json::Json};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    models::ApiResponse,
+    models::book::{CreateBookRequest, ImportBookForm, ImportBookResponse},
+    database::get_pool,
+    validation::{self, validation_error},
+    errors::{ApiResult, AppError},
+    guards::AuthorUser,
+    import::{self, ImportFormat},
+    handlers::books::insert_book,
+};
+
+// Limite dos campos de título e autor do livro
+const MAX_TITLE_CHARS: usize = 255;
+
+// Formato do arquivo pela extensão do nome original ou pelo tipo informado no upload
+fn detect_format(form: &ImportBookForm<'_>) -> Option<ImportFormat> {
+    let extension = form
+        .file
+        .raw_name()
+        .map(|name| name.dangerous_unsafe_unsanitized_raw().as_str())
+        .and_then(|name| name.rsplit_once('.'))
+        .and_then(|(_, extension)| ImportFormat::from_extension(extension));
+
+    extension.or_else(|| {
+        form.file
+            .content_type()
+            .and_then(|ct| ImportFormat::from_media_type(&format!("{}/{}", ct.top(), ct.sub())))
+    })
+}
+
+// Cortar valores longos demais, avisando o usuário
+fn fit(value: String, field: &str, imported: &mut import::ImportedBook) -> String {
+    if value.chars().count() <= MAX_TITLE_CHARS {
+        return value;
+    }
+    imported.warn(
+        "FIELD_TRUNCATED",
+        format!("O {} foi cortado em {} caracteres", field, MAX_TITLE_CHARS),
+    );
+    value.chars().take(MAX_TITLE_CHARS).collect()
+}
+
+// Importar manuscrito (.md, .txt, .docx ou .epub) criando o livro com seus capítulos
+#[post("/books/import", data = "<form>")]
+pub async fn import_book(author: AuthorUser, form: Form<ImportBookForm<'_>>) -> ApiResult<ImportBookResponse> {
+    let pool = get_pool();
+    let category_id = match Uuid::parse_str(&form.category_id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let Some(format) = detect_format(&form) else {
+        return Err(AppError::new(
+            Status::UnsupportedMediaType,
+            "UNSUPPORTED_FORMAT",
+            "Formato não suportado (use .md, .txt, .docx ou .epub)",
+        ));
+    };
+
+    let mut bytes = Vec::new();
+    let mut file = form.file.open().await.map_err(|e| AppError::internal("Erro ao abrir arquivo enviado", e))?;
+    file.read_to_end(&mut bytes).await.map_err(|e| AppError::internal("Erro ao ler arquivo enviado", e))?;
+
+    // A leitura de .docx/.epub é custosa para arquivos grandes: fora das threads do servidor
+    let parsed = tokio::task::spawn_blocking(move || import::parse(format, &bytes))
+        .await
+        .map_err(|e| AppError::internal("Erro ao importar arquivo", e))?;
+    let mut imported = match parsed {
+        Ok(imported) => imported,
+        Err(e) => {
+            return Err(AppError::unprocessable(
+                "INVALID_IMPORT_FILE",
+                format!("Não foi possível ler o arquivo: {}", e),
+            ))
+        }
+    };
+
+    let content = imported.content();
+    if content.trim().is_empty() {
+        return Err(AppError::unprocessable("EMPTY_IMPORT", "O arquivo não contém texto para importar"));
+    }
+
+    let title = match form.title.clone().filter(|t| !t.trim().is_empty()).or(imported.title.take()) {
+        Some(title) => title,
+        None => {
+            imported.warn("MISSING_TITLE", "Título não encontrado no arquivo; usando o nome do arquivo");
+            form.file.name().filter(|n| !n.is_empty()).unwrap_or("Livro importado").replace(['_', '-'], " ")
+        }
+    };
+    let book_author = match form.author.clone().filter(|a| !a.trim().is_empty()).or(imported.author.take()) {
+        Some(name) => name,
+        None => {
+            imported.warn("MISSING_AUTHOR", "Autor não encontrado no arquivo; usando o nome do usuário");
+            author.0.name.clone()
+        }
+    };
+    let isbn = match imported.isbn.take() {
+        Some(isbn) if validation::isbn(&isbn).is_ok() => Some(isbn),
+        Some(isbn) => {
+            imported.warn("INVALID_ISBN", format!("ISBN '{}' do arquivo é inválido e foi ignorado", isbn));
+            None
+        }
+        None => None,
+    };
+
+    let book = CreateBookRequest {
+        title: fit(title, "título", &mut imported),
+        author: fit(book_author, "autor", &mut imported),
+        isbn,
+        description: imported.description.take(),
+        content,
+        content_format: format.content_format(),
+        category_id,
+        is_public: form.is_public.unwrap_or(false),
+    };
+    book.validate().map_err(|e| validation_error(&e))?;
+
+    let new_book = insert_book(&book, author.0.id).await?;
+
+    let chapters = match sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM chapters WHERE book_id = $1")
+        .bind(new_book.id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => return Err(AppError::internal("Erro ao contar capítulos", e)),
+    };
+
+    let response = ImportBookResponse {
+        book: new_book,
+        format: format.as_str(),
+        chapters,
+        warnings: imported.warnings,
+    };
+    Ok(Json(ApiResponse::success(response, "Livro importado com sucesso")))
+}
@@ -0,0 +1,111 @@
+use rocket::{get, post, put, serde::json::Json};
+use bcrypt::{hash, verify, DEFAULT_COST};
+use crate::{
+    models::{ApiResponse, ChangePasswordRequest, UpdateUserRequest, UserResponse},
+    models::book::BookWithCategory,
+    database::get_pool,
+    validation::Valid,
+    errors::{ApiResult, AppError},
+    guards::{AuthUser, SessionUser},
+    handlers::{auth::{check_send_limit, send_stats_query, send_verification_email, SendStats}, users::apply_user_update},
+    tokens,
+};
+
+// Perfil do usuário autenticado
+#[get("/me")]
+pub async fn get_me(auth: AuthUser) -> ApiResult<UserResponse> {
+    Ok(Json(ApiResponse::success(UserResponse::from(auth.0), "Perfil encontrado")))
+}
+
+// Atualizar perfil do usuário autenticado
+#[put("/me", data = "<user_data>")]
+pub async fn update_me(auth: SessionUser, user_data: Valid<UpdateUserRequest>) -> ApiResult<UserResponse> {
+    let updated_user = apply_user_update(auth.0.id, &user_data.into_inner()).await?;
+    Ok(Json(ApiResponse::success(UserResponse::from(updated_user), "Perfil atualizado com sucesso")))
+}
+
+// Trocar senha do usuário autenticado
+#[put("/me/password", data = "<password_data>")]
+pub async fn change_password(auth: SessionUser, password_data: Valid<ChangePasswordRequest>) -> ApiResult<()> {
+    let pool = get_pool();
+    let request = &password_data.into_inner();
+
+    // Confirmar a senha atual antes de trocar
+    match verify(&request.current_password, &auth.0.password_hash) {
+        Ok(true) => {}
+        // Hash inválido (conta sem senha definida) não confere com nenhuma senha
+        Ok(false) | Err(_) => return Err(AppError::unauthorized("WRONG_PASSWORD", "Senha atual incorreta")),
+    }
+
+    let password_hash = hash(&request.new_password, DEFAULT_COST)
+        .map_err(|e| AppError::internal("Erro ao gerar hash da senha", e))?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<(), sqlx::Error> = async {
+        sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+            .bind(&password_hash)
+            .bind(auth.0.id)
+            .execute(&mut *tx)
+            .await?;
+
+        // Encerrar sessões e tokens de acesso criados com a senha antiga
+        tokens::revoke_credentials(&mut tx, auth.0.id).await
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar troca de senha", e))?;
+            Ok(Json(ApiResponse::success((), "Senha alterada com sucesso")))
+        }
+        Err(e) => Err(AppError::internal("Erro ao alterar senha", e)),
+    }
+}
+
+// Reenviar o email de verificação, com intervalo mínimo entre envios e limite por hora
+#[post("/me/verification-email")]
+pub async fn resend_verification_email(auth: SessionUser) -> ApiResult<()> {
+    let pool = get_pool();
+
+    if auth.0.is_email_verified() {
+        return Err(AppError::conflict("EMAIL_ALREADY_VERIFIED", "Seu email já foi confirmado"));
+    }
+
+    let stats = match sqlx::query_as::<_, SendStats>(&send_stats_query("email_verification_tokens", "user_id"))
+        .bind(auth.0.id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(stats) => stats,
+        Err(e) => return Err(AppError::internal("Erro ao verificar envios anteriores", e)),
+    };
+    check_send_limit("VERIFICATION_THROTTLED", stats)?;
+
+    send_verification_email(&auth.0).await?;
+    Ok(Json(ApiResponse::success((), "Email de verificação enviado")))
+}
+
+// Listar livros do usuário autenticado, incluindo os privados
+#[get("/me/books")]
+pub async fn get_my_books(auth: AuthUser) -> ApiResult<Vec<BookWithCategory>> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, BookWithCategory>(
+        r#"
+        SELECT b.*, c.id as cat_id, c.name as cat_name, c.description as cat_description, 
+               c.created_at as cat_created_at, c.updated_at as cat_updated_at
+        FROM books b
+        JOIN categories c ON b.category_id = c.id
+        WHERE b.user_id = $1
+        ORDER BY b.updated_at DESC
+        "#
+    )
+    .bind(auth.0.id)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(books) => Ok(Json(ApiResponse::success(books, "Seus livros listados com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar livros do usuário", e)),
+    }
+}
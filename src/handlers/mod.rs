@@ -1,3 +1,12 @@
 pub mod users;
 pub mod auth;
 pub mod books;
+pub mod me;
+pub mod progress;
+pub mod stats;
+pub mod chapters;
+pub mod revisions;
+pub mod export;
+pub mod import;
+pub mod two_factor;
+pub mod access_tokens;
@@ -0,0 +1,160 @@
+use rocket::{get, put, serde::json::Json};
+use uuid::Uuid;
+use crate::{
+    models::ApiResponse,
+    models::book::{ContinueReadingItem, ReadingProgress, UpdateProgressRequest},
+    database::get_pool,
+    validation::Valid,
+    errors::{ApiResult, AppError},
+    guards::AuthUser,
+    handlers::books::check_book_visible,
+};
+
+// Progresso de leitura do usuário autenticado em um livro
+#[get("/books/<id>/progress")]
+pub async fn get_progress(auth: AuthUser, id: String) -> ApiResult<ReadingProgress> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    check_book_visible(book_id, Some(&auth)).await?;
+
+    match sqlx::query_as::<_, ReadingProgress>(
+        "SELECT * FROM reading_progress WHERE user_id = $1 AND book_id = $2"
+    )
+    .bind(auth.0.id)
+    .bind(book_id)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(progress) => Ok(Json(ApiResponse::success(progress, "Progresso encontrado"))),
+        Err(sqlx::Error::RowNotFound) => {
+            Err(AppError::not_found("PROGRESS_NOT_FOUND", "Nenhum progresso registrado para este livro"))
+        }
+        Err(e) => Err(AppError::internal("Erro ao buscar progresso", e)),
+    }
+}
+
+// Registrar progresso de leitura (cria ou atualiza o registro do par usuário/livro).
+// Chegar à última página marca o livro como concluído; a página atual é limitada ao total,
+// que por padrão é a contagem de páginas do livro.
+#[put("/books/<id>/progress", data = "<progress_data>")]
+pub async fn update_progress(auth: AuthUser, id: String, progress_data: Valid<UpdateProgressRequest>) -> ApiResult<ReadingProgress> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+    let progress = progress_data.into_inner();
+
+    check_book_visible(book_id, Some(&auth)).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<ReadingProgress, sqlx::Error> = async {
+        // Sem total informado, as páginas são as do livro (calculadas a partir do conteúdo)
+        let page_count = sqlx::query_scalar::<_, i32>("SELECT page_count FROM books WHERE id = $1")
+            .bind(book_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let total_pages = progress.total_pages.or(Some(page_count).filter(|pages| *pages > 0));
+
+        // Estado anterior, para registrar no histórico apenas o que mudou
+        let previous = sqlx::query_as::<_, (i32, bool)>(
+            "SELECT current_page, is_completed FROM reading_progress WHERE user_id = $1 AND book_id = $2 FOR UPDATE"
+        )
+        .bind(auth.0.id)
+        .bind(book_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let updated = sqlx::query_as::<_, ReadingProgress>(
+            r#"
+            INSERT INTO reading_progress (user_id, book_id, current_page, total_pages, is_completed, last_read_at)
+            VALUES (
+                $1, $2,
+                CASE WHEN $4::integer IS NULL THEN $3 ELSE LEAST($3, $4) END,
+                COALESCE($4, 0),
+                COALESCE($5, false) OR ($4 IS NOT NULL AND $3 >= $4),
+                NOW()
+            )
+            ON CONFLICT (user_id, book_id) DO UPDATE SET
+                current_page = CASE
+                    WHEN COALESCE($4, reading_progress.total_pages) > 0
+                        THEN LEAST($3, COALESCE($4, reading_progress.total_pages))
+                    ELSE $3
+                END,
+                total_pages = COALESCE($4, reading_progress.total_pages),
+                is_completed = COALESCE($5, reading_progress.is_completed)
+                    OR (COALESCE($4, reading_progress.total_pages) > 0 AND $3 >= COALESCE($4, reading_progress.total_pages)),
+                last_read_at = NOW(),
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(auth.0.id)
+        .bind(book_id)
+        .bind(progress.current_page)
+        .bind(total_pages)
+        .bind(progress.is_completed)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (previous_page, was_completed) = previous.unwrap_or((0, false));
+        let pages_read = (updated.current_page - previous_page).max(0);
+        let finished = updated.is_completed && !was_completed;
+
+        if pages_read > 0 || finished {
+            sqlx::query("INSERT INTO reading_log (user_id, book_id, pages_read, finished) VALUES ($1, $2, $3, $4)")
+                .bind(auth.0.id)
+                .bind(book_id)
+                .bind(pages_read)
+                .bind(finished)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Ok(updated)
+    }
+    .await;
+
+    match result {
+        Ok(progress) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar progresso", e))?;
+            Ok(Json(ApiResponse::success(progress, "Progresso atualizado com sucesso")))
+        }
+        Err(e) => Err(AppError::internal("Erro ao atualizar progresso", e)),
+    }
+}
+
+// Livros em andamento do usuário autenticado, do mais recente para o mais antigo
+#[get("/me/continue-reading?<limit>")]
+pub async fn continue_reading(auth: AuthUser, limit: Option<i64>) -> ApiResult<Vec<ContinueReadingItem>> {
+    let pool = get_pool();
+    let limit = limit.unwrap_or(10).clamp(1, 50);
+
+    match sqlx::query_as::<_, ContinueReadingItem>(
+        r#"
+        SELECT rp.book_id, b.title, b.author, c.name as cat_name,
+               rp.current_page, rp.total_pages, rp.last_read_at
+        FROM reading_progress rp
+        JOIN books b ON rp.book_id = b.id
+        JOIN categories c ON b.category_id = c.id
+        WHERE rp.user_id = $1
+          AND rp.is_completed = false
+          AND ((b.is_public = true AND b.status = 'published') OR b.user_id = $1)
+        ORDER BY rp.last_read_at DESC
+        LIMIT $2
+        "#
+    )
+    .bind(auth.0.id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(items) => Ok(Json(ApiResponse::success(items, "Leituras em andamento listadas com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar leituras em andamento", e)),
+    }
+}
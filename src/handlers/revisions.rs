@@ -0,0 +1,232 @@
+use rocket::{get, post, serde::json::Json};
+use sqlx::PgConnection;
+use similar::{ChangeTag, TextDiff};
+use uuid::Uuid;
+use crate::{
+    models::ApiResponse,
+    models::book::{BookRevision, BookRevisionSummary, DiffSegment, RevisionDiff},
+    database::get_pool,
+    errors::{ApiResult, AppError},
+    guards::AuthUser,
+    handlers::{books::check_book_permission, chapters::split_book_chapters},
+};
+
+// Colunas da revisão com o nome de quem salvou
+const REVISION_COLUMNS: &str = r#"
+    r.id, r.book_id, r.revision_number, r.author_id, u.name as author_name,
+    r.word_count, r.word_delta, r.created_at
+"#;
+
+fn revision_not_found() -> AppError {
+    AppError::not_found("REVISION_NOT_FOUND", "Revisão não encontrada")
+}
+
+// Bloquear o livro até o fim da transação, serializando as alterações de
+// conteúdo (e a numeração das revisões) de um mesmo livro
+pub(crate) async fn lock_book(conn: &mut PgConnection, book_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT id FROM books WHERE id = $1 FOR UPDATE")
+        .bind(book_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+// Registrar o conteúdo atual do livro como nova revisão, se ele mudou desde a
+// última. Roda na transação de quem alterou o conteúdo; o bloqueio do livro
+// impede que dois salvamentos calculem o mesmo número de revisão.
+pub(crate) async fn record_revision(conn: &mut PgConnection, book_id: Uuid, author_id: Uuid) -> Result<(), sqlx::Error> {
+    lock_book(&mut *conn, book_id).await?;
+
+    // A contagem de palavras é a mesma de books.word_count
+    sqlx::query(
+        r#"
+        INSERT INTO book_revisions (book_id, revision_number, author_id, content, word_count, word_delta)
+        SELECT b.id, COALESCE(last.revision_number, 0) + 1, $2, b.content,
+               b.word_count, b.word_count - COALESCE(last.word_count, 0)
+        FROM books b
+        LEFT JOIN LATERAL (
+            SELECT revision_number, word_count, content
+            FROM book_revisions
+            WHERE book_id = b.id
+            ORDER BY revision_number DESC
+            LIMIT 1
+        ) last ON true
+        WHERE b.id = $1 AND last.content IS DISTINCT FROM b.content
+        "#
+    )
+    .bind(book_id)
+    .bind(author_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+async fn fetch_revision(book_id: Uuid, number: i32) -> Result<BookRevision, AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, BookRevision>(&format!(
+        r#"
+        SELECT {}, r.content
+        FROM book_revisions r
+        LEFT JOIN users u ON r.author_id = u.id
+        WHERE r.book_id = $1 AND r.revision_number = $2
+        "#,
+        REVISION_COLUMNS
+    ))
+    .bind(book_id)
+    .bind(number)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(revision) => Ok(revision),
+        Err(sqlx::Error::RowNotFound) => Err(revision_not_found()),
+        Err(e) => Err(AppError::internal("Erro ao buscar revisão", e)),
+    }
+}
+
+// Listar revisões do livro, da mais recente para a mais antiga (dono ou administradores)
+#[get("/books/<id>/revisions")]
+pub async fn get_revisions(auth: AuthUser, id: String) -> ApiResult<Vec<BookRevisionSummary>> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    check_book_permission(book_id, &auth).await?;
+
+    match sqlx::query_as::<_, BookRevisionSummary>(&format!(
+        r#"
+        SELECT {}
+        FROM book_revisions r
+        LEFT JOIN users u ON r.author_id = u.id
+        WHERE r.book_id = $1
+        ORDER BY r.revision_number DESC
+        "#,
+        REVISION_COLUMNS
+    ))
+    .bind(book_id)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(revisions) => Ok(Json(ApiResponse::success(revisions, "Revisões listadas com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar revisões", e)),
+    }
+}
+
+// Diff entre duas revisões, por palavra ou por linha (padrão)
+#[get("/books/<id>/revisions/diff?<from>&<to>&<mode>")]
+pub async fn diff_revisions(auth: AuthUser, id: String, from: i32, to: i32, mode: Option<String>) -> ApiResult<RevisionDiff> {
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+    let mode = mode.unwrap_or_else(|| "line".to_string());
+    if mode != "line" && mode != "word" {
+        return Err(AppError::bad_request("INVALID_DIFF_MODE", "O modo do diff deve ser 'line' ou 'word'"));
+    }
+
+    check_book_permission(book_id, &auth).await?;
+
+    let old = fetch_revision(book_id, from).await?;
+    let new = fetch_revision(book_id, to).await?;
+
+    let diff = if mode == "word" {
+        TextDiff::from_words(&old.content, &new.content)
+    } else {
+        TextDiff::from_lines(&old.content, &new.content)
+    };
+
+    // Agrupar mudanças consecutivas do mesmo tipo em um único trecho
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    let mut insertions = 0;
+    let mut deletions = 0;
+    for change in diff.iter_all_changes() {
+        let tag = match change.tag() {
+            ChangeTag::Equal => "equal",
+            ChangeTag::Insert => {
+                insertions += 1;
+                "insert"
+            }
+            ChangeTag::Delete => {
+                deletions += 1;
+                "delete"
+            }
+        };
+        match segments.last_mut() {
+            Some(last) if last.tag == tag => last.value.push_str(change.value()),
+            _ => segments.push(DiffSegment { tag, value: change.value().to_string() }),
+        }
+    }
+
+    let response = RevisionDiff { from, to, mode, insertions, deletions, segments };
+    Ok(Json(ApiResponse::success(response, "Diff calculado com sucesso")))
+}
+
+// Buscar revisão com o conteúdo completo
+#[get("/books/<id>/revisions/<number>", rank = 2)]
+pub async fn get_revision(auth: AuthUser, id: String, number: i32) -> ApiResult<BookRevision> {
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    check_book_permission(book_id, &auth).await?;
+
+    let revision = fetch_revision(book_id, number).await?;
+    Ok(Json(ApiResponse::success(revision, "Revisão encontrada")))
+}
+
+// Restaurar uma revisão antiga; o conteúdo restaurado entra como nova revisão
+#[post("/books/<id>/revisions/<number>/restore")]
+pub async fn restore_revision(auth: AuthUser, id: String, number: i32) -> ApiResult<BookRevisionSummary> {
+    let pool = get_pool();
+    let book_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    check_book_permission(book_id, &auth).await?;
+
+    let revision = fetch_revision(book_id, number).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<(), sqlx::Error> = async {
+        lock_book(&mut tx, book_id).await?;
+
+        sqlx::query("UPDATE books SET content = $1, updated_at = NOW() WHERE id = $2")
+            .bind(&revision.content)
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await?;
+
+        split_book_chapters(&mut *tx, book_id).await?;
+        record_revision(&mut tx, book_id, auth.0.id).await
+    }
+    .await;
+
+    match result {
+        Ok(()) => tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar restauração", e))?,
+        Err(e) => return Err(AppError::internal("Erro ao restaurar revisão", e)),
+    }
+
+    match sqlx::query_as::<_, BookRevisionSummary>(&format!(
+        r#"
+        SELECT {}
+        FROM book_revisions r
+        LEFT JOIN users u ON r.author_id = u.id
+        WHERE r.book_id = $1
+        ORDER BY r.revision_number DESC
+        LIMIT 1
+        "#,
+        REVISION_COLUMNS
+    ))
+    .bind(book_id)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(latest) => Ok(Json(ApiResponse::success(latest, "Revisão restaurada com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar revisão restaurada", e)),
+    }
+}
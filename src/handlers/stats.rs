@@ -0,0 +1,122 @@
+use rocket::{get, serde::json::Json};
+use uuid::Uuid;
+use crate::{
+    models::ApiResponse,
+    models::book::{ReadingPeriod, ReadingStats, UserStatsResponse},
+    database::get_pool,
+    errors::{ApiResult, AppError},
+    guards::AuthUser,
+};
+
+// Quantidade padrão de semanas e meses na série histórica
+const DEFAULT_WEEKS: i64 = 12;
+const DEFAULT_MONTHS: i64 = 12;
+
+// Totais de leitura e escrita do usuário
+async fn reading_stats(user_id: Uuid) -> Result<ReadingStats, AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, ReadingStats>(
+        r#"
+        SELECT $1 AS user_id,
+               (SELECT COUNT(*) FROM reading_progress WHERE user_id = $1) AS total_books,
+               (SELECT COUNT(*) FROM reading_progress WHERE user_id = $1 AND is_completed = true) AS books_read,
+               (SELECT COUNT(*) FROM books WHERE user_id = $1) AS books_created,
+               (SELECT COALESCE(SUM(current_page), 0)::bigint FROM reading_progress WHERE user_id = $1) AS total_pages,
+               (
+                   SELECT c.name
+                   FROM reading_progress rp
+                   JOIN books b ON rp.book_id = b.id
+                   JOIN categories c ON b.category_id = c.id
+                   WHERE rp.user_id = $1
+                   GROUP BY c.name
+                   ORDER BY COUNT(*) DESC, c.name
+                   LIMIT 1
+               ) AS favorite_category
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(stats) => Ok(stats),
+        Err(e) => Err(AppError::internal("Erro ao calcular estatísticas", e)),
+    }
+}
+
+// Série histórica por semana ou mês, incluindo períodos sem leitura.
+// `unit` é uma unidade do date_trunc do PostgreSQL ("week" ou "month").
+async fn reading_series(user_id: Uuid, unit: &str, periods: i64) -> Result<Vec<ReadingPeriod>, AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, ReadingPeriod>(
+        r#"
+        SELECT s.period_start::date AS period_start,
+               COUNT(l.id) FILTER (WHERE l.finished) AS books_finished,
+               COALESCE(SUM(l.pages_read), 0)::bigint AS pages_read
+        FROM generate_series(
+            date_trunc($2, NOW()) - ($3 - 1) * ('1 ' || $2)::interval,
+            date_trunc($2, NOW()),
+            ('1 ' || $2)::interval
+        ) AS s(period_start)
+        LEFT JOIN reading_log l
+               ON l.user_id = $1 AND date_trunc($2, l.created_at) = s.period_start
+        GROUP BY s.period_start
+        ORDER BY s.period_start
+        "#
+    )
+    .bind(user_id)
+    .bind(unit)
+    .bind(periods)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(series) => Ok(series),
+        Err(e) => Err(AppError::internal("Erro ao calcular série de leitura", e)),
+    }
+}
+
+async fn user_stats(user_id: Uuid, weeks: Option<i64>, months: Option<i64>) -> Result<UserStatsResponse, AppError> {
+    let weeks = weeks.unwrap_or(DEFAULT_WEEKS).clamp(1, 104);
+    let months = months.unwrap_or(DEFAULT_MONTHS).clamp(1, 60);
+
+    Ok(UserStatsResponse {
+        stats: reading_stats(user_id).await?,
+        weekly: reading_series(user_id, "week", weeks).await?,
+        monthly: reading_series(user_id, "month", months).await?,
+    })
+}
+
+// Estatísticas do usuário autenticado
+#[get("/me/stats?<weeks>&<months>")]
+pub async fn get_my_stats(auth: AuthUser, weeks: Option<i64>, months: Option<i64>) -> ApiResult<UserStatsResponse> {
+    let stats = user_stats(auth.0.id, weeks, months).await?;
+    Ok(Json(ApiResponse::success(stats, "Estatísticas calculadas com sucesso")))
+}
+
+// Estatísticas de um usuário (o próprio usuário ou administradores)
+#[get("/users/<id>/stats?<weeks>&<months>")]
+pub async fn get_user_stats(auth: AuthUser, id: String, weeks: Option<i64>, months: Option<i64>) -> ApiResult<UserStatsResponse> {
+    let pool = get_pool();
+    let user_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    if !auth.can_manage(user_id) {
+        return Err(AppError::forbidden("FORBIDDEN", "Você não pode ver as estatísticas de outro usuário"));
+    }
+
+    match sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+        Err(e) => return Err(AppError::internal("Erro ao buscar usuário", e)),
+    }
+
+    let stats = user_stats(user_id, weeks, months).await?;
+    Ok(Json(ApiResponse::success(stats, "Estatísticas calculadas com sucesso")))
+}
@@ -0,0 +1,270 @@
+use rocket::{post, serde::json::Json};
+use bcrypt::verify;
+use chrono::Utc;
+use uuid::Uuid;
+use crate::{
+    models::{
+        ApiResponse, DisableTwoFactorRequest, RecoveryCodesResponse, TwoFactorCodeRequest, TwoFactorSetupResponse, User,
+    },
+    database::get_pool,
+    validation::Valid,
+    errors::{ApiResult, AppError},
+    guards::SessionUser,
+    two_factor::{self, normalize_code},
+    tokens,
+};
+
+fn invalid_code() -> AppError {
+    AppError::unauthorized("INVALID_TWO_FACTOR_CODE", "Código de autenticação inválido")
+}
+
+// Conferir um código do aplicativo autenticador. O passo de tempo aceito é
+// gravado atomicamente, então o mesmo código não vale duas vezes.
+async fn verify_totp(user: &User, code: &str) -> Result<bool, AppError> {
+    let pool = get_pool();
+
+    let step = match &user.totp_secret {
+        Some(secret) => two_factor::matching_step(secret, &normalize_code(code), Utc::now().timestamp()),
+        None => None,
+    };
+    let step = match step {
+        Some(step) => step,
+        None => return Ok(false),
+    };
+
+    match sqlx::query(
+        "UPDATE users SET totp_last_step = $1 WHERE id = $2 AND (totp_last_step IS NULL OR totp_last_step < $1)"
+    )
+    .bind(step)
+    .bind(user.id)
+    .execute(pool)
+    .await
+    {
+        Ok(result) => Ok(result.rows_affected() == 1),
+        Err(e) => Err(AppError::internal("Erro ao registrar código de autenticação", e)),
+    }
+}
+
+// Consumir um código de recuperação ainda não usado
+async fn use_recovery_code(user_id: Uuid, code: &str) -> Result<bool, AppError> {
+    let pool = get_pool();
+
+    match sqlx::query(
+        "UPDATE recovery_codes SET used_at = NOW() WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL"
+    )
+    .bind(user_id)
+    .bind(tokens::hash_token(&normalize_code(code)))
+    .execute(pool)
+    .await
+    {
+        Ok(result) if result.rows_affected() > 0 => {
+            log_info!("Código de recuperação usado pelo usuário {}", user_id);
+            Ok(true)
+        }
+        Ok(_) => Ok(false),
+        Err(e) => Err(AppError::internal("Erro ao verificar código de recuperação", e)),
+    }
+}
+
+// Segundo fator de uma conta com 2FA ativo: código do aplicativo ou de recuperação
+pub(crate) async fn verify_second_factor(user: &User, code: &str) -> Result<bool, AppError> {
+    if !user.has_two_factor() {
+        return Ok(false);
+    }
+    if verify_totp(user, code).await? {
+        return Ok(true);
+    }
+    use_recovery_code(user.id, code).await
+}
+
+// Substituir os códigos de recuperação do usuário por um novo lote
+async fn replace_recovery_codes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    let codes = two_factor::generate_recovery_codes();
+    let hashes: Vec<String> = codes.iter().map(|code| tokens::hash_token(&normalize_code(code))).collect();
+
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query("INSERT INTO recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::varchar[])")
+        .bind(user_id)
+        .bind(&hashes)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(codes)
+}
+
+// Iniciar o cadastro do 2FA: gera um segredo pendente até a confirmação.
+// Chamar de novo antes de confirmar substitui o segredo anterior.
+#[post("/me/2fa/setup")]
+pub async fn setup_two_factor(auth: SessionUser) -> ApiResult<TwoFactorSetupResponse> {
+    let pool = get_pool();
+
+    if auth.0.has_two_factor() {
+        return Err(AppError::conflict("TWO_FACTOR_ALREADY_ENABLED", "A autenticação em dois fatores já está ativa"));
+    }
+
+    let secret = two_factor::generate_secret();
+    let provisioning_uri = two_factor::provisioning_uri(&secret, &auth.0.email)
+        .ok_or_else(|| AppError::internal("Erro ao gerar URI do autenticador", "segredo inválido"))?;
+
+    match sqlx::query(
+        "UPDATE users SET totp_secret = $1, totp_last_step = NULL, updated_at = NOW() WHERE id = $2 AND totp_enabled_at IS NULL"
+    )
+    .bind(&secret)
+    .bind(auth.0.id)
+    .execute(pool)
+    .await
+    {
+        Ok(_) => Ok(Json(ApiResponse::success(
+            TwoFactorSetupResponse { secret, provisioning_uri },
+            "Cadastre a conta no aplicativo autenticador e confirme com um código",
+        ))),
+        Err(e) => Err(AppError::internal("Erro ao iniciar autenticação em dois fatores", e)),
+    }
+}
+
+// Confirmar o cadastro com um código do aplicativo; ativa o 2FA e devolve
+// os códigos de recuperação (exibidos apenas agora)
+#[post("/me/2fa/confirm", data = "<code_data>")]
+pub async fn confirm_two_factor(auth: SessionUser, code_data: Valid<TwoFactorCodeRequest>) -> ApiResult<RecoveryCodesResponse> {
+    let pool = get_pool();
+    let request = code_data.into_inner();
+
+    if auth.0.has_two_factor() {
+        return Err(AppError::conflict("TWO_FACTOR_ALREADY_ENABLED", "A autenticação em dois fatores já está ativa"));
+    }
+    let secret = match &auth.0.totp_secret {
+        Some(secret) => secret.clone(),
+        None => return Err(AppError::bad_request(
+            "TWO_FACTOR_NOT_STARTED",
+            "Inicie o cadastro da autenticação em dois fatores antes de confirmar",
+        )),
+    };
+
+    if !verify_totp(&auth.0, &request.code).await? {
+        return Err(invalid_code());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<Vec<String>, sqlx::Error> = async {
+        // O segredo confirmado precisa ser o mesmo do código conferido
+        sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE users SET totp_enabled_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND totp_secret = $2 AND totp_enabled_at IS NULL
+            RETURNING id
+            "#
+        )
+        .bind(auth.0.id)
+        .bind(&secret)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        replace_recovery_codes(&mut tx, auth.0.id).await
+    }
+    .await;
+
+    match result {
+        Ok(recovery_codes) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar ativação do 2FA", e))?;
+            Ok(Json(ApiResponse::success(
+                RecoveryCodesResponse { recovery_codes },
+                "Autenticação em dois fatores ativada. Guarde os códigos de recuperação em local seguro",
+            )))
+        }
+        Err(sqlx::Error::RowNotFound) => Err(AppError::conflict(
+            "TWO_FACTOR_SETUP_CHANGED",
+            "O cadastro da autenticação em dois fatores mudou; inicie novamente",
+        )),
+        Err(e) => Err(AppError::internal("Erro ao ativar autenticação em dois fatores", e)),
+    }
+}
+
+// Gerar novos códigos de recuperação; os anteriores deixam de valer
+#[post("/me/2fa/recovery-codes", data = "<code_data>")]
+pub async fn regenerate_recovery_codes(auth: SessionUser, code_data: Valid<TwoFactorCodeRequest>) -> ApiResult<RecoveryCodesResponse> {
+    let pool = get_pool();
+    let request = code_data.into_inner();
+
+    if !auth.0.has_two_factor() {
+        return Err(AppError::bad_request("TWO_FACTOR_NOT_ENABLED", "A autenticação em dois fatores não está ativa"));
+    }
+    if !verify_second_factor(&auth.0, &request.code).await? {
+        return Err(invalid_code());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    match replace_recovery_codes(&mut tx, auth.0.id).await {
+        Ok(recovery_codes) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar códigos de recuperação", e))?;
+            Ok(Json(ApiResponse::success(
+                RecoveryCodesResponse { recovery_codes },
+                "Novos códigos de recuperação gerados",
+            )))
+        }
+        Err(e) => Err(AppError::internal("Erro ao gerar códigos de recuperação", e)),
+    }
+}
+
+// Desativar o 2FA: exige a senha atual e um código (do aplicativo ou de recuperação)
+#[post("/me/2fa/disable", data = "<disable_data>")]
+pub async fn disable_two_factor(auth: SessionUser, disable_data: Valid<DisableTwoFactorRequest>) -> ApiResult<()> {
+    let pool = get_pool();
+    let request = disable_data.into_inner();
+
+    if !auth.0.has_two_factor() {
+        return Err(AppError::bad_request("TWO_FACTOR_NOT_ENABLED", "A autenticação em dois fatores não está ativa"));
+    }
+
+    match verify(&request.password, &auth.0.password_hash) {
+        Ok(true) => {}
+        // Hash inválido (conta sem senha definida) não confere com nenhuma senha
+        Ok(false) | Err(_) => return Err(AppError::unauthorized("WRONG_PASSWORD", "Senha atual incorreta")),
+    }
+    if !verify_second_factor(&auth.0, &request.code).await? {
+        return Err(invalid_code());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let result: Result<(), sqlx::Error> = async {
+        sqlx::query(
+            r#"
+            UPDATE users SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(auth.0.id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+            .bind(auth.0.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM mfa_challenges WHERE user_id = $1")
+            .bind(auth.0.id)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar desativação do 2FA", e))?;
+            Ok(Json(ApiResponse::success((), "Autenticação em dois fatores desativada")))
+        }
+        Err(e) => Err(AppError::internal("Erro ao desativar autenticação em dois fatores", e)),
+    }
+}
@@ -1,14 +1,22 @@
-use rocket::{get, post, put, delete, http::Status, serde::json::Json};
+use rocket::{get, post, put, delete, serde::json::Json};
 use uuid::Uuid;
 use bcrypt::{hash, DEFAULT_COST};
 use crate::{
-    models::{User, CreateUserRequest, UpdateUserRequest, ApiResponse, UserResponse},
+    models::{
+        User, CreateUserRequest, UpdateUserRequest, UpdateRoleRequest, ApiResponse, UserResponse, Role,
+        AccountLockoutEvent,
+    },
     database::get_pool,
+    validation::Valid,
+    errors::{is_unique_violation, ApiResult, AppError},
+    guards::AdminUser,
+    handlers::auth::send_verification_email,
+    login_throttle,
 };
 
 // Listar todos os usuários
 #[get("/users")]
-pub async fn get_users() -> Result<Json<ApiResponse<Vec<UserResponse>>>, Status> {
+pub async fn get_users(_admin: AdminUser) -> ApiResult<Vec<UserResponse>> {
     let pool = get_pool();
     
     match sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY created_at DESC")
@@ -19,21 +27,72 @@ pub async fn get_users() -> Result<Json<ApiResponse<Vec<UserResponse>>>, Status>
             let user_responses: Vec<UserResponse> = users.into_iter().map(UserResponse::from).collect();
             Ok(Json(ApiResponse::success(user_responses, "Usuários listados com sucesso")))
         },
-        Err(e) => {
-            eprintln!("Erro ao buscar usuários: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Err(e) => Err(AppError::internal("Erro ao buscar usuários", e)),
+    }
+}
+
+// Histórico de bloqueios e desbloqueios de contas (os 100 mais recentes).
+// Com ?active=true, apenas os bloqueios ainda em vigor.
+#[get("/users/lockouts?<active>")]
+pub async fn get_lockout_events(_admin: AdminUser, active: Option<bool>) -> ApiResult<Vec<AccountLockoutEvent>> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, AccountLockoutEvent>(
+        r#"
+        SELECT * FROM (
+            SELECT e.id, e.email, e.user_id, e.event, e.locked_until, e.ip_address, e.actor_id, e.created_at,
+                   COALESCE(e.event = 'locked' AND t.locked_until = e.locked_until AND t.locked_until > NOW(), FALSE) AS active
+            FROM account_lockout_events e
+            LEFT JOIN login_throttles t ON t.key = 'email:' || e.email
+        ) events
+        WHERE NOT $1 OR active
+        ORDER BY created_at DESC
+        LIMIT 100
+        "#
+    )
+    .bind(active.unwrap_or(false))
+    .fetch_all(pool)
+    .await
+    {
+        Ok(events) => Ok(Json(ApiResponse::success(events, "Bloqueios de conta listados com sucesso"))),
+        Err(e) => Err(AppError::internal("Erro ao buscar bloqueios de conta", e)),
+    }
+}
+
+// Desbloquear uma conta antes do fim do bloqueio
+#[post("/users/<id>/unlock")]
+pub async fn unlock_user(admin: AdminUser, id: String) -> ApiResult<()> {
+    let pool = get_pool();
+    let user_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let user = match sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(user) => user,
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+        Err(e) => return Err(AppError::internal("Erro ao buscar usuário", e)),
+    };
+
+    if login_throttle::unlock(&user.email, user.id, admin.0.id).await? {
+        Ok(Json(ApiResponse::success((), "Conta desbloqueada com sucesso")))
+    } else {
+        Err(AppError::conflict("ACCOUNT_NOT_LOCKED", "A conta não está bloqueada"))
     }
 }
 
 // Buscar usuário por ID
 #[get("/users/<id>")]
 // #[get("/users/<Name>")]
-pub async fn get_user(id: String) -> Result<Json<ApiResponse<UserResponse>>, Status> {
+pub async fn get_user(_admin: AdminUser, id: String) -> ApiResult<UserResponse> {
     let pool = get_pool();
     let user_id = match Uuid::parse_str(&id) {
         Ok(id) => id,
-        Err(_) => return Err(Status::BadRequest),
+        Err(_) => return Err(AppError::invalid_id()),
     };
 
     match sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
@@ -42,57 +101,60 @@ pub async fn get_user(id: String) -> Result<Json<ApiResponse<UserResponse>>, Sta
         .await
     {
         Ok(user) => Ok(Json(ApiResponse::success(UserResponse::from(user), "Usuário encontrado"))),
-        Err(sqlx::Error::RowNotFound) => Err(Status::NotFound),
-        Err(e) => {
-            eprintln!("Erro ao buscar usuário: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Err(sqlx::Error::RowNotFound) => Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao buscar usuário", e)),
     }
 }
 
 // Criar novo usuário
 #[post("/users", data = "<user_data>")]
-pub async fn create_user(user_data: Json<CreateUserRequest>) -> Result<Json<ApiResponse<UserResponse>>, Status> {
+pub async fn create_user(_admin: AdminUser, user_data: Valid<CreateUserRequest>) -> ApiResult<UserResponse> {
     let pool = get_pool();
     let user = &user_data.into_inner();
 
     // Hash da senha
-    let password_hash = match hash(&user.password, DEFAULT_COST) {
-        Ok(hash) => hash,
-        Err(_) => return Err(Status::InternalServerError),
-    };
+    let password_hash = hash(&user.password, DEFAULT_COST)
+        .map_err(|e| AppError::internal("Erro ao gerar hash da senha", e))?;
 
     match sqlx::query_as::<_, User>(
-        "INSERT INTO users (name, email, password_hash, age) VALUES ($1, $2, $3, $4) RETURNING *"
+        "INSERT INTO users (name, email, password_hash, age, role) VALUES ($1, $2, $3, $4, $5) RETURNING *"
     )
     .bind(&user.name)
     .bind(&user.email)
     .bind(&password_hash)
-    .bind(&user.age)
+    .bind(user.age)
+    .bind(user.role.unwrap_or_default())
     .fetch_one(pool)
     .await
     {
-        Ok(new_user) => Ok(Json(ApiResponse::success(UserResponse::from(new_user), "Usuário criado com sucesso"))),
-        Err(sqlx::Error::Database(db_err)) if db_err.constraint().is_some() => {
-            Err(Status::Conflict) // Email duplicado
+        Ok(new_user) => {
+            send_verification_email(&new_user).await?;
+            Ok(Json(ApiResponse::success(UserResponse::from(new_user), "Usuário criado com sucesso")))
         }
-        Err(e) => {
-            eprintln!("Erro ao criar usuário: {}", e);
-            Err(Status::InternalServerError)
+        Err(e) if is_unique_violation(&e) => {
+            Err(AppError::conflict("EMAIL_TAKEN", "Este email já está cadastrado"))
         }
+        Err(e) => Err(AppError::internal("Erro ao criar usuário", e)),
     }
 }
 
-// Atualizar usuário
-#[put("/users/<id>", data = "<user_data>")]
-pub async fn update_user(id: String, user_data: Json<UpdateUserRequest>) -> Result<Json<ApiResponse<User>>, Status> {
+// Aplicar atualização parcial em um usuário (compartilhado com /me). Um email
+// novo perde a confirmação e recebe o link de verificação.
+pub(crate) async fn apply_user_update(user_id: Uuid, user: &UpdateUserRequest) -> Result<User, AppError> {
     let pool = get_pool();
-    let user_id = match Uuid::parse_str(&id) {
-        Ok(id) => id,
-        Err(_) => return Err(Status::BadRequest),
-    };
 
-    let user = &user_data.into_inner();
+    let previous_email = match user.email {
+        Some(_) => match sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+        {
+            Ok(email) => Some(email),
+            Err(sqlx::Error::RowNotFound) => return Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+            Err(e) => return Err(AppError::internal("Erro ao buscar usuário", e)),
+        },
+        None => None,
+    };
 
     // Construir query dinamicamente baseada nos campos fornecidos
     let mut query = "UPDATE users SET ".to_string();
@@ -104,7 +166,11 @@ pub async fn update_user(id: String, user_data: Json<UpdateUserRequest>) -> Resu
         param_count += 1;
     }
     if let Some(ref _email) = user.email {
-        params.push(format!("email = ${}", param_count));
+        // Um email novo precisa ser verificado de novo
+        params.push(format!(
+            "email_verified_at = CASE WHEN email = ${0} THEN email_verified_at END, email = ${0}",
+            param_count
+        ));
         param_count += 1;
     }
     if let Some(_age) = user.age {
@@ -113,10 +179,10 @@ pub async fn update_user(id: String, user_data: Json<UpdateUserRequest>) -> Resu
     }
 
     if params.is_empty() {
-        return Err(Status::BadRequest);
+        return Err(AppError::bad_request("NO_FIELDS", "Nenhum campo para atualizar"));
     }
 
-    params.push(format!("updated_at = NOW()"));
+    params.push("updated_at = NOW()".to_string());
     query.push_str(&params.join(", "));
     query.push_str(&format!(" WHERE id = ${} RETURNING *", param_count));
 
@@ -134,25 +200,69 @@ pub async fn update_user(id: String, user_data: Json<UpdateUserRequest>) -> Resu
     query_builder = query_builder.bind(user_id);
 
     match query_builder.fetch_one(pool).await {
-        Ok(updated_user) => Ok(Json(ApiResponse::success(updated_user, "Usuário atualizado com sucesso"))),
-        Err(sqlx::Error::RowNotFound) => Err(Status::NotFound),
-        Err(sqlx::Error::Database(db_err)) if db_err.constraint().is_some() => {
-            Err(Status::Conflict) // Email duplicado
+        Ok(updated_user) => {
+            if previous_email.is_some_and(|email| email != updated_user.email) {
+                send_verification_email(&updated_user).await?;
+            }
+            Ok(updated_user)
         }
-        Err(e) => {
-            eprintln!("Erro ao atualizar usuário: {}", e);
-            Err(Status::InternalServerError)
+        Err(sqlx::Error::RowNotFound) => Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+        Err(e) if is_unique_violation(&e) => {
+            Err(AppError::conflict("EMAIL_TAKEN", "Este email já está cadastrado"))
         }
+        Err(e) => Err(AppError::internal("Erro ao atualizar usuário", e)),
+    }
+}
+
+// Atualizar usuário
+#[put("/users/<id>", data = "<user_data>")]
+pub async fn update_user(_admin: AdminUser, id: String, user_data: Valid<UpdateUserRequest>) -> ApiResult<UserResponse> {
+    let user_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let updated_user = apply_user_update(user_id, &user_data.into_inner()).await?;
+    Ok(Json(ApiResponse::success(UserResponse::from(updated_user), "Usuário atualizado com sucesso")))
+}
+
+// Promover ou rebaixar usuário (apenas administradores)
+#[put("/users/<id>/role", data = "<role_data>")]
+pub async fn update_user_role(admin: AdminUser, id: String, role_data: Json<UpdateRoleRequest>) -> ApiResult<UserResponse> {
+    let pool = get_pool();
+    let user_id = match Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => return Err(AppError::invalid_id()),
+    };
+
+    let role = role_data.into_inner().role;
+
+    // Impedir que o administrador remova o próprio acesso
+    if user_id == admin.0.id && role != Role::Admin {
+        return Err(AppError::bad_request("CANNOT_DEMOTE_SELF", "Você não pode remover seu próprio acesso de administrador"));
+    }
+
+    match sqlx::query_as::<_, User>(
+        "UPDATE users SET role = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+    )
+    .bind(role)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(updated_user) => Ok(Json(ApiResponse::success(UserResponse::from(updated_user), "Papel do usuário atualizado com sucesso"))),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao atualizar papel do usuário", e)),
     }
 }
 
 // Deletar usuário
 #[delete("/users/<id>")]
-pub async fn delete_user(id: String) -> Result<Json<ApiResponse<()>>, Status> {
+pub async fn delete_user(_admin: AdminUser, id: String) -> ApiResult<()> {
     let pool = get_pool();
     let user_id = match Uuid::parse_str(&id) {
         Ok(id) => id,
-        Err(_) => return Err(Status::BadRequest),
+        Err(_) => return Err(AppError::invalid_id()),
     };
 
     match sqlx::query("DELETE FROM users WHERE id = $1")
@@ -163,10 +273,7 @@ pub async fn delete_user(id: String) -> Result<Json<ApiResponse<()>>, Status> {
         Ok(result) if result.rows_affected() > 0 => {
             Ok(Json(ApiResponse::success((), "Usuário deletado com sucesso")))
         }
-        Ok(_) => Err(Status::NotFound),
-        Err(e) => {
-            eprintln!("Erro ao deletar usuário: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Ok(_) => Err(AppError::not_found("USER_NOT_FOUND", "Usuário não encontrado")),
+        Err(e) => Err(AppError::internal("Erro ao deletar usuário", e)),
     }
 }
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use roxmltree::{Document, Node};
+use crate::import::{open_archive, read_entry, split_text_chapters, ImportError, ImportedBook, ImportedChapter};
+
+const WORD_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+enum ParagraphStyle {
+    Title,
+    Heading(u8),
+}
+
+enum Block {
+    Title(String),
+    Heading(u8, String),
+    Text(String),
+}
+
+fn word_attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((WORD_NS, name))
+}
+
+fn is_word(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().namespace() == Some(WORD_NS) && node.tag_name().name() == name
+}
+
+fn parse_xml<'a>(xml: &'a str, name: &str) -> Result<Document<'a>, ImportError> {
+    Document::parse(xml).map_err(|e| ImportError(format!("{} inválido ({})", name, e)))
+}
+
+// Nomes dos estilos de parágrafo (ex.: "Heading1" -> "heading 1")
+fn style_names(styles: Option<&str>) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let Some(doc) = styles.and_then(|xml| Document::parse(xml).ok()) else {
+        return names;
+    };
+    for style in doc.descendants().filter(|n| is_word(n, "style")) {
+        let name = style.children().find(|n| is_word(n, "name")).and_then(|n| word_attr(n, "val"));
+        if let (Some(id), Some(name)) = (word_attr(style, "styleId"), name) {
+            names.insert(id.to_string(), name.to_lowercase());
+        }
+    }
+    names
+}
+
+// Tipo do parágrafo pelo estilo ("Title", "heading N") ou nível de estrutura
+fn paragraph_style(paragraph: Node, styles: &HashMap<String, String>) -> Option<ParagraphStyle> {
+    let properties = paragraph.children().find(|n| is_word(n, "pPr"))?;
+    if let Some(style_id) = properties.children().find(|n| is_word(n, "pStyle")).and_then(|n| word_attr(n, "val")) {
+        let name = styles.get(style_id).cloned().unwrap_or_else(|| style_id.to_lowercase());
+        if name == "title" || name == "título" {
+            return Some(ParagraphStyle::Title);
+        }
+        let level = name
+            .strip_prefix("heading")
+            .or_else(|| name.strip_prefix("título"))
+            .or_else(|| name.strip_prefix("ttulo"))
+            .and_then(|n| n.trim().parse::<u8>().ok());
+        if let Some(level) = level {
+            return Some(ParagraphStyle::Heading(level));
+        }
+    }
+    properties
+        .children()
+        .find(|n| is_word(n, "outlineLvl"))
+        .and_then(|n| word_attr(n, "val"))
+        .and_then(|v| v.parse::<u8>().ok())
+        .map(|level| ParagraphStyle::Heading(level + 1))
+}
+
+fn paragraph_text(paragraph: Node) -> String {
+    let mut text = String::new();
+    for node in paragraph.descendants() {
+        if is_word(&node, "t") {
+            text.push_str(node.text().unwrap_or(""));
+        } else if is_word(&node, "tab") {
+            text.push('\t');
+        } else if is_word(&node, "br") || is_word(&node, "cr") {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+// DOCX: metadados em docProps/core.xml, capítulos nos parágrafos com
+// estilo de título de nível mais alto usado no documento
+pub fn parse(bytes: &[u8]) -> Result<ImportedBook, ImportError> {
+    let mut archive = open_archive(bytes)?;
+    let document = read_entry(&mut archive, "word/document.xml")?
+        .ok_or_else(|| ImportError("word/document.xml não encontrado (não é um DOCX)".to_string()))?;
+    let styles = read_entry(&mut archive, "word/styles.xml")?;
+    let core = read_entry(&mut archive, "docProps/core.xml")?;
+
+    let mut book = ImportedBook::default();
+
+    if let Some(core) = core.as_deref().and_then(|xml| Document::parse(xml).ok()) {
+        let dc = |name: &str| {
+            core.descendants()
+                .find(|n| n.tag_name().namespace() == Some(DC_NS) && n.tag_name().name() == name)
+                .and_then(|n| n.text())
+                .map(str::to_string)
+        };
+        book.title = dc("title");
+        book.author = dc("creator");
+        book.description = dc("description");
+    }
+
+    let styles = style_names(styles.as_deref());
+    let doc = parse_xml(&document, "word/document.xml")?;
+    let Some(body) = doc.descendants().find(|n| is_word(n, "body")) else {
+        return Err(ImportError("word/document.xml sem corpo".to_string()));
+    };
+
+    let mut blocks = Vec::new();
+    for paragraph in body.descendants().filter(|n| is_word(n, "p")) {
+        let text = paragraph_text(paragraph);
+        blocks.push(match paragraph_style(paragraph, &styles) {
+            Some(ParagraphStyle::Title) => Block::Title(text),
+            Some(ParagraphStyle::Heading(level)) if !text.trim().is_empty() => Block::Heading(level, text),
+            _ => Block::Text(text),
+        });
+    }
+
+    let images = body.descendants().filter(|n| is_word(n, "drawing") || is_word(n, "pict")).count();
+    if images > 0 {
+        book.warn("IMAGES_IGNORED", format!("{} imagem(ns) do documento não foram importadas", images));
+    }
+    if body.descendants().any(|n| is_word(&n, "tbl")) {
+        book.warn("TABLES_FLATTENED", "Tabelas foram importadas como parágrafos de texto");
+    }
+    if body.descendants().any(|n| is_word(&n, "footnoteReference") || is_word(&n, "endnoteReference")) {
+        book.warn("FOOTNOTES_IGNORED", "Notas de rodapé não foram importadas");
+    }
+
+    let chapter_level = blocks
+        .iter()
+        .filter_map(|b| match b {
+            Block::Heading(level, _) => Some(*level),
+            _ => None,
+        })
+        .min();
+
+    let Some(chapter_level) = chapter_level else {
+        let text: Vec<String> = blocks
+            .into_iter()
+            .filter_map(|b| match b {
+                Block::Title(title) => {
+                    book.title.get_or_insert(title);
+                    None
+                }
+                Block::Text(text) | Block::Heading(_, text) => Some(text),
+            })
+            .collect();
+        book.chapters = split_text_chapters(&text.join("\n\n"));
+        return Ok(book);
+    };
+
+    let mut chapters = vec![ImportedChapter::default()];
+    for block in blocks {
+        match block {
+            Block::Title(title) => {
+                if book.title.is_none() {
+                    book.title = Some(title);
+                }
+            }
+            Block::Heading(level, title) if level == chapter_level => {
+                chapters.push(ImportedChapter { title: Some(title), content: String::new() });
+            }
+            Block::Heading(_, text) | Block::Text(text) => {
+                if let Some(current) = chapters.last_mut() {
+                    current.content.push_str(&text);
+                    current.content.push_str("\n\n");
+                }
+            }
+        }
+    }
+    book.chapters = chapters;
+    Ok(book)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::zip_package;
+
+    const STYLES: &str = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+        <w:style w:styleId="Ttulo1"><w:name w:val="heading 1"/></w:style>
+        <w:style w:styleId="Ttulo2"><w:name w:val="heading 2"/></w:style>
+        <w:style w:styleId="Title"><w:name w:val="Title"/></w:style>
+    </w:styles>"#;
+
+    const CORE: &str = r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties"
+        xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:creator>Ana</dc:creator>
+    </cp:coreProperties>"#;
+
+    fn paragraph(style: Option<&str>, text: &str) -> String {
+        let properties = style.map_or(String::new(), |s| format!(r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, s));
+        format!("<w:p>{}<w:r><w:t>{}</w:t></w:r></w:p>", properties, text)
+    }
+
+    fn document(paragraphs: &[String]) -> String {
+        format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            paragraphs.concat()
+        )
+    }
+
+    #[test]
+    fn splits_on_highest_heading_style() {
+        let document = document(&[
+            paragraph(Some("Title"), "O Livro"),
+            paragraph(None, "Prefácio."),
+            paragraph(Some("Ttulo2"), "Antes"),
+            paragraph(Some("Ttulo1"), "Um"),
+            paragraph(None, "Texto um."),
+            paragraph(Some("Ttulo2"), "Seção"),
+            paragraph(Some("Ttulo1"), "Dois"),
+        ]);
+        let bytes = zip_package(&[
+            ("word/document.xml", &document),
+            ("word/styles.xml", STYLES),
+            ("docProps/core.xml", CORE),
+        ]);
+
+        let book = parse(&bytes).unwrap();
+        assert_eq!(book.title.as_deref(), Some("O Livro"));
+        assert_eq!(book.author.as_deref(), Some("Ana"));
+        let titles: Vec<_> = book.chapters.iter().map(|c| c.title.as_deref()).collect();
+        assert_eq!(titles, vec![None, Some("Um"), Some("Dois")]);
+        assert_eq!(book.chapters[0].content, "Prefácio.\n\nAntes\n\n");
+        assert_eq!(book.chapters[1].content, "Texto um.\n\nSeção\n\n");
+    }
+
+    #[test]
+    fn without_heading_styles_uses_chapter_lines() {
+        let document = document(&[paragraph(None, "Capítulo 1: A"), paragraph(None, "a")]);
+        let bytes = zip_package(&[("word/document.xml", &document)]);
+
+        let book = parse(&bytes).unwrap();
+        let titles: Vec<_> = book.chapters.iter().map(|c| c.title.as_deref()).collect();
+        assert_eq!(titles, vec![None, Some("A")]);
+        assert_eq!(book.chapters[1].content.trim(), "a");
+    }
+
+    #[test]
+    fn outline_level_counts_as_heading() {
+        let document = document(&[
+            r#"<w:p><w:pPr><w:outlineLvl w:val="0"/></w:pPr><w:r><w:t>Um</w:t></w:r></w:p>"#.to_string(),
+            paragraph(None, "x"),
+        ]);
+        let bytes = zip_package(&[("word/document.xml", &document)]);
+
+        let book = parse(&bytes).unwrap();
+        assert_eq!(book.chapters[1].title.as_deref(), Some("Um"));
+    }
+
+    #[test]
+    fn rejects_package_without_document() {
+        let bytes = zip_package(&[("word/styles.xml", STYLES)]);
+        assert!(parse(&bytes).is_err());
+    }
+}
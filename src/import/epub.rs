@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use roxmltree::{Document, Node, ParsingOptions};
+use crate::import::{open_archive, read_entry, ImportError, ImportedBook, ImportedChapter};
+
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const OPS_NS: &str = "http://www.idpf.org/2007/ops";
+
+// Entidades HTML comuns que não existem em XML puro
+const HTML_ENTITIES: &[(&str, &str)] = &[
+    ("&nbsp;", "&#160;"),
+    ("&mdash;", "&#8212;"),
+    ("&ndash;", "&#8211;"),
+    ("&hellip;", "&#8230;"),
+    ("&laquo;", "&#171;"),
+    ("&raquo;", "&#187;"),
+    ("&ldquo;", "&#8220;"),
+    ("&rdquo;", "&#8221;"),
+    ("&lsquo;", "&#8216;"),
+    ("&rsquo;", "&#8217;"),
+    ("&copy;", "&#169;"),
+];
+
+// Documentos que não fazem parte do texto (capa, folha de rosto, sumário)
+const SKIPPED_TYPES: &[&str] = &["cover", "titlepage", "toc", "landmarks"];
+
+// Elementos cujo conteúdo vira um parágrafo
+const BLOCKS: &[&str] = &["p", "li", "pre", "blockquote", "dt", "dd", "figcaption", "td", "th"];
+
+fn parse_xml(xml: &str) -> Result<Document<'_>, roxmltree::Error> {
+    Document::parse_with_options(xml, ParsingOptions { allow_dtd: true, ..ParsingOptions::default() })
+}
+
+fn local_name<'a>(node: &Node<'a, '_>) -> &'a str {
+    node.tag_name().name()
+}
+
+// Resolver caminho relativo ao diretório do arquivo base (ex.: "OEBPS/")
+fn resolve(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href).replace("%20", " ");
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn element_text(node: Node) -> String {
+    let mut text = String::new();
+    for child in node.descendants() {
+        if child.is_text() {
+            text.push_str(child.text().unwrap_or(""));
+        } else if child.is_element() && local_name(&child) == "br" {
+            text.push('\n');
+        }
+    }
+    text.lines().map(|line| line.split_whitespace().collect::<Vec<_>>().join(" ")).collect::<Vec<_>>().join("\n")
+}
+
+enum Block {
+    Heading(u8, String),
+    Text(String),
+}
+
+// Percorrer o corpo do documento coletando títulos e parágrafos em ordem
+fn collect_blocks(node: Node, blocks: &mut Vec<Block>, images: &mut usize) {
+    for child in node.children().filter(|n| n.is_element()) {
+        let name = local_name(&child);
+        match name {
+            "script" | "style" => {}
+            "img" | "image" | "svg" => *images += 1,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                let text = element_text(child);
+                if !text.trim().is_empty() {
+                    blocks.push(Block::Heading(level, text));
+                }
+            }
+            _ if BLOCKS.contains(&name) && !child.children().any(|n| n.is_element() && BLOCKS.contains(&local_name(&n))) => {
+                *images += child.descendants().filter(|n| n.is_element() && matches!(local_name(n), "img" | "image")).count();
+                let text = element_text(child);
+                if !text.trim().is_empty() {
+                    blocks.push(Block::Text(text));
+                }
+            }
+            _ => collect_blocks(child, blocks, images),
+        }
+    }
+}
+
+// EPUB: metadados do pacote OPF e um ou mais capítulos por documento do
+// spine, divididos no nível de título mais alto de cada documento
+pub fn parse(bytes: &[u8]) -> Result<ImportedBook, ImportError> {
+    let mut archive = open_archive(bytes)?;
+    let container = read_entry(&mut archive, "META-INF/container.xml")?
+        .ok_or_else(|| ImportError("META-INF/container.xml não encontrado (não é um EPUB)".to_string()))?;
+    let container = parse_xml(&container).map_err(|e| ImportError(format!("container.xml inválido ({})", e)))?;
+    let opf_path = container
+        .descendants()
+        .find(|n| n.is_element() && local_name(n) == "rootfile")
+        .and_then(|n| n.attribute("full-path"))
+        .ok_or_else(|| ImportError("container.xml sem rootfile".to_string()))?
+        .to_string();
+
+    let opf = read_entry(&mut archive, &opf_path)?
+        .ok_or_else(|| ImportError(format!("{} não encontrado", opf_path)))?;
+    let opf = parse_xml(&opf).map_err(|e| ImportError(format!("{} inválido ({})", opf_path, e)))?;
+    let base_dir = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    let mut book = ImportedBook::default();
+    let dc = |name: &str| {
+        opf.descendants()
+            .filter(|n| n.tag_name().namespace() == Some(DC_NS) && n.tag_name().name() == name)
+            .filter_map(|n| n.text())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    book.title = dc("title").into_iter().next();
+    book.author = dc("creator").into_iter().next();
+    book.description = dc("description").into_iter().next();
+    book.isbn = dc("identifier").into_iter().find_map(|id| {
+        let id = id.trim();
+        id.strip_prefix("urn:isbn:").or_else(|| id.strip_prefix("isbn:")).map(str::to_string)
+    });
+
+    // id -> (caminho, propriedades)
+    let manifest: HashMap<&str, (String, &str)> = opf
+        .descendants()
+        .filter(|n| n.is_element() && local_name(n) == "item")
+        .filter_map(|n| {
+            let media_type = n.attribute("media-type")?;
+            if media_type != "application/xhtml+xml" {
+                return None;
+            }
+            Some((n.attribute("id")?, (resolve(base_dir, n.attribute("href")?), n.attribute("properties").unwrap_or(""))))
+        })
+        .collect();
+
+    let spine: Vec<&str> = opf
+        .descendants()
+        .filter(|n| n.is_element() && local_name(n) == "itemref" && n.attribute("linear") != Some("no"))
+        .filter_map(|n| n.attribute("idref"))
+        .collect();
+
+    let mut images = 0;
+    for idref in spine {
+        let Some((path, properties)) = manifest.get(idref) else { continue };
+        if properties.split_whitespace().any(|p| p == "nav") {
+            continue;
+        }
+        let Some(xhtml) = read_entry(&mut archive, path)? else {
+            book.warn("MISSING_SECTION", format!("{} listado no spine não existe no arquivo", path));
+            continue;
+        };
+        let xhtml = HTML_ENTITIES.iter().fold(xhtml, |xml, (entity, code)| xml.replace(entity, code));
+        let doc = match parse_xml(&xhtml) {
+            Ok(doc) => doc,
+            Err(e) => {
+                book.warn("UNREADABLE_SECTION", format!("{} não pôde ser lido e foi ignorado ({})", path, e));
+                continue;
+            }
+        };
+
+        let skipped = doc.descendants().any(|n| {
+            n.attribute((OPS_NS, "type"))
+                .is_some_and(|t| t.split_whitespace().any(|t| SKIPPED_TYPES.contains(&t)))
+        });
+        if skipped {
+            continue;
+        }
+        let Some(body) = doc.descendants().find(|n| n.is_element() && local_name(n) == "body") else { continue };
+
+        let mut blocks = Vec::new();
+        collect_blocks(body, &mut blocks, &mut images);
+        let chapter_level = blocks
+            .iter()
+            .filter_map(|b| match b {
+                Block::Heading(level, _) => Some(*level),
+                _ => None,
+            })
+            .min();
+
+        // Texto antes do primeiro título de um documento continua o capítulo anterior
+        for block in blocks {
+            match block {
+                Block::Heading(level, title) if Some(level) == chapter_level => {
+                    book.chapters.push(ImportedChapter { title: Some(title), content: String::new() });
+                }
+                Block::Heading(_, text) | Block::Text(text) => {
+                    if book.chapters.is_empty() {
+                        book.chapters.push(ImportedChapter::default());
+                    }
+                    if let Some(current) = book.chapters.last_mut() {
+                        current.content.push_str(&text);
+                        current.content.push_str("\n\n");
+                    }
+                }
+            }
+        }
+    }
+
+    if images > 0 {
+        book.warn("IMAGES_IGNORED", format!("{} imagem(ns) do EPUB não foram importadas", images));
+    }
+    Ok(book)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::zip_package;
+
+    const CONTAINER: &str = r#"<container xmlns="urn:oasis:names:tc:opendocument:xmlns:container" version="1.0">
+        <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+    </container>"#;
+
+    const OPF: &str = r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+        <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+            <dc:title>O Livro</dc:title>
+            <dc:creator>Ana</dc:creator>
+            <dc:identifier>urn:isbn:9780306406157</dc:identifier>
+        </metadata>
+        <manifest>
+            <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+            <item id="cover" href="text/cover.xhtml" media-type="application/xhtml+xml"/>
+            <item id="c1" href="text/c1.xhtml" media-type="application/xhtml+xml"/>
+            <item id="c2" href="text/c2.xhtml" media-type="application/xhtml+xml"/>
+        </manifest>
+        <spine><itemref idref="nav"/><itemref idref="cover"/><itemref idref="c1"/><itemref idref="c2"/></spine>
+    </package>"#;
+
+    fn xhtml(body: &str) -> String {
+        format!(
+            r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops"><body>{}</body></html>"#,
+            body
+        )
+    }
+
+    #[test]
+    fn reads_metadata_and_splits_on_highest_heading_level() {
+        let nav = xhtml("<nav><h1>Sumário</h1></nav>");
+        let cover = xhtml(r#"<section epub:type="cover"><h1>Capa</h1></section>"#);
+        // O primeiro título do documento é de nível menor que o dos capítulos
+        let c1 = xhtml("<h3>Epígrafe</h3><p>Citação&nbsp;curta.</p><h1>Um</h1><p>Texto um.</p><h2>Seção</h2><p>Mais.</p>");
+        let c2 = xhtml(r#"<p>Continua um.</p><h1>Dois</h1><p>Fim<br/>linha.</p><img src="a.png"/>"#);
+        let bytes = zip_package(&[
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", OPF),
+            ("OEBPS/nav.xhtml", &nav),
+            ("OEBPS/text/cover.xhtml", &cover),
+            ("OEBPS/text/c1.xhtml", &c1),
+            ("OEBPS/text/c2.xhtml", &c2),
+        ]);
+
+        let book = parse(&bytes).unwrap();
+        assert_eq!(book.title.as_deref(), Some("O Livro"));
+        assert_eq!(book.author.as_deref(), Some("Ana"));
+        assert_eq!(book.isbn.as_deref(), Some("9780306406157"));
+
+        let titles: Vec<_> = book.chapters.iter().map(|c| c.title.as_deref()).collect();
+        assert_eq!(titles, vec![None, Some("Um"), Some("Dois")]);
+        assert_eq!(book.chapters[0].content, "Epígrafe\n\nCitação curta.\n\n");
+        assert_eq!(book.chapters[1].content, "Texto um.\n\nSeção\n\nMais.\n\nContinua um.\n\n");
+        assert_eq!(book.chapters[2].content, "Fim\nlinha.\n\n");
+        assert_eq!(book.warnings[0].code, "IMAGES_IGNORED");
+    }
+
+    #[test]
+    fn warns_about_missing_spine_documents() {
+        let bytes = zip_package(&[("META-INF/container.xml", CONTAINER), ("OEBPS/content.opf", OPF)]);
+
+        let book = parse(&bytes).unwrap();
+        assert!(book.chapters.is_empty());
+        assert!(book.warnings.iter().all(|w| w.code == "MISSING_SECTION"));
+        assert_eq!(book.warnings.len(), 3);
+    }
+
+    #[test]
+    fn rejects_package_without_container() {
+        let bytes = zip_package(&[("OEBPS/content.opf", OPF)]);
+        assert!(parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn resolves_paths_relative_to_package() {
+        assert_eq!(resolve("OEBPS", "text/c1.xhtml#top"), "OEBPS/text/c1.xhtml");
+        assert_eq!(resolve("OEBPS/text", "../img/a%20b.png"), "OEBPS/img/a b.png");
+        assert_eq!(resolve("", "./c1.xhtml"), "c1.xhtml");
+    }
+}
@@ -0,0 +1,309 @@
+// Importação de manuscritos: Markdown, texto puro, DOCX e EPUB
+mod docx;
+mod epub;
+mod text;
+
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+use crate::models::book::{ContentFormat, ImportWarning};
+
+// Limites do conteúdo descompactado de .docx/.epub (proteção contra zip bombs):
+// por arquivo do pacote e somando todos os arquivos lidos
+const MAX_ENTRY_BYTES: u64 = 50 * 1024 * 1024;
+const MAX_ARCHIVE_BYTES: u64 = 200 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Markdown,
+    Text,
+    Docx,
+    Epub,
+}
+
+impl ImportFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(ImportFormat::Markdown),
+            "txt" => Some(ImportFormat::Text),
+            "docx" => Some(ImportFormat::Docx),
+            "epub" => Some(ImportFormat::Epub),
+            _ => None,
+        }
+    }
+
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "text/markdown" | "text/x-markdown" => Some(ImportFormat::Markdown),
+            "text/plain" => Some(ImportFormat::Text),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some(ImportFormat::Docx),
+            "application/epub+zip" => Some(ImportFormat::Epub),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Markdown => "markdown",
+            ImportFormat::Text => "text",
+            ImportFormat::Docx => "docx",
+            ImportFormat::Epub => "epub",
+        }
+    }
+
+    // Só o Markdown mantém a marcação; os demais formatos viram texto puro
+    pub fn content_format(&self) -> ContentFormat {
+        match self {
+            ImportFormat::Markdown => ContentFormat::Markdown,
+            _ => ContentFormat::Plain,
+        }
+    }
+}
+
+// Capítulo encontrado no arquivo; `None` é o texto antes do primeiro título
+#[derive(Debug, Default)]
+pub struct ImportedChapter {
+    pub title: Option<String>,
+    pub content: String,
+}
+
+// Resultado da leitura do arquivo, antes de criar o livro
+#[derive(Debug, Default)]
+pub struct ImportedBook {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub isbn: Option<String>,
+    pub chapters: Vec<ImportedChapter>,
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl ImportedBook {
+    pub fn warn(&mut self, code: &'static str, message: impl Into<String>) {
+        self.warnings.push(ImportWarning { code, message: message.into() });
+    }
+
+    // Conteúdo completo no formato usado pelo restante da aplicação:
+    // "Capítulo N: título" antes de cada capítulo, separados por linhas em branco
+    pub fn content(&self) -> String {
+        let mut parts = Vec::new();
+        let mut number = 0;
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            match &chapter.title {
+                Some(title) => {
+                    number += 1;
+                    parts.push(format!("Capítulo {}: {}", number, title));
+                }
+                // Sem título só é possível no início (vira a introdução)
+                None if i > 0 => {
+                    number += 1;
+                    parts.push(format!("Capítulo {}:", number));
+                }
+                None => {}
+            }
+            if !chapter.content.trim().is_empty() {
+                parts.push(chapter.content.trim().to_string());
+            }
+        }
+        parts.join("\n\n")
+    }
+}
+
+// Falha que impede a importação (arquivo corrompido ou incompleto)
+#[derive(Debug)]
+pub struct ImportError(pub String);
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Ler o arquivo no formato informado
+pub fn parse(format: ImportFormat, bytes: &[u8]) -> Result<ImportedBook, ImportError> {
+    let mut book = match format {
+        ImportFormat::Markdown => text::parse_markdown(bytes),
+        ImportFormat::Text => text::parse_text(bytes),
+        ImportFormat::Docx => docx::parse(bytes)?,
+        ImportFormat::Epub => epub::parse(bytes)?,
+    };
+
+    for field in [&mut book.title, &mut book.author, &mut book.description, &mut book.isbn] {
+        *field = field.take().map(|value| collapse_whitespace(&value)).filter(|value| !value.is_empty());
+    }
+    book.chapters.retain(|c| c.title.is_some() || !c.content.trim().is_empty());
+    if !book.chapters.iter().any(|c| c.title.is_some()) {
+        book.warn("NO_CHAPTERS", "Nenhum título de capítulo encontrado; o texto foi importado como um único capítulo");
+    }
+    Ok(book)
+}
+
+// Texto do arquivo em UTF-8; arquivos em outra codificação são lidos como Latin-1
+fn decode_text(bytes: &[u8], book: &mut ImportedBook) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.replace("\r\n", "\n"),
+        Err(_) => {
+            book.warn("ENCODING_FALLBACK", "O arquivo não está em UTF-8 e foi lido como Latin-1");
+            bytes.iter().map(|&b| b as char).collect::<String>().replace("\r\n", "\n")
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Título no padrão "Capítulo N: título" / "Chapter N - título" (N em algarismos
+// arábicos ou romanos). Retorna o título, vazio quando só há o número.
+fn chapter_heading(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let lower = trimmed.to_lowercase();
+    let prefix = ["capítulo", "capitulo", "chapter"].into_iter().find(|p| lower.starts_with(p))?;
+
+    let rest = &trimmed[trimmed.char_indices().nth(prefix.chars().count()).map_or(trimmed.len(), |(i, _)| i)..];
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let number_len = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+    let number = &rest[..number_len];
+    let is_number = !number.is_empty()
+        && (number.chars().all(|c| c.is_ascii_digit())
+            || number.chars().all(|c| "ivxlcdmIVXLCDM".contains(c)));
+    if !is_number {
+        return None;
+    }
+
+    let title = rest[number_len..].trim_start_matches(|c: char| c.is_whitespace() || ":.-–—".contains(c));
+    Some(title.trim().to_string())
+}
+
+// Dividir texto corrido nos títulos de capítulo
+fn split_text_chapters(text: &str) -> Vec<ImportedChapter> {
+    let mut chapters = vec![ImportedChapter::default()];
+    for line in text.lines() {
+        match chapter_heading(line) {
+            Some(title) => chapters.push(ImportedChapter { title: Some(title), content: String::new() }),
+            None => {
+                if let Some(current) = chapters.last_mut() {
+                    current.content.push_str(line);
+                    current.content.push('\n');
+                }
+            }
+        }
+    }
+    chapters
+}
+
+// Pacote aberto, com quanto ainda pode ser descompactado pelas leituras
+struct Archive<'a> {
+    zip: ZipArchive<Cursor<&'a [u8]>>,
+    remaining_bytes: u64,
+}
+
+fn open_archive(bytes: &[u8]) -> Result<Archive<'_>, ImportError> {
+    ZipArchive::new(Cursor::new(bytes))
+        .map(|zip| Archive { zip, remaining_bytes: MAX_ARCHIVE_BYTES })
+        .map_err(|e| ImportError(format!("arquivo compactado inválido ({})", e)))
+}
+
+// Ler um arquivo de texto de dentro do pacote; `None` se não existir
+fn read_entry(archive: &mut Archive, name: &str) -> Result<Option<String>, ImportError> {
+    let entry = match archive.zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(ImportError(format!("erro ao ler {} ({})", name, e))),
+    };
+    let remaining = archive.remaining_bytes;
+    let mut bytes = Vec::new();
+    entry
+        .take(MAX_ENTRY_BYTES.min(remaining) + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| ImportError(format!("erro ao ler {} ({})", name, e)))?;
+    if bytes.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(ImportError(format!("{} excede o tamanho máximo permitido", name)));
+    }
+    if bytes.len() as u64 > remaining {
+        return Err(ImportError("o conteúdo descompactado do arquivo excede o tamanho máximo permitido".to_string()));
+    }
+    archive.remaining_bytes -= bytes.len() as u64;
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|_| ImportError(format!("{} não está em UTF-8", name)))
+}
+
+// Pacote .zip em memória para os testes dos leitores de DOCX e EPUB
+#[cfg(test)]
+fn zip_package(entries: &[(&str, &str)]) -> Vec<u8> {
+    use std::io::Write;
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in entries {
+        writer
+            .start_file(*name, FileOptions::default().compression_method(CompressionMethod::Stored))
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chapter_heading_reads_title_after_number() {
+        assert_eq!(chapter_heading("Capítulo 1: O começo"), Some("O começo".to_string()));
+        assert_eq!(chapter_heading("  capitulo 10. Depois  "), Some("Depois".to_string()));
+        assert_eq!(chapter_heading("Chapter IV - The End"), Some("The End".to_string()));
+        assert_eq!(chapter_heading("CAPÍTULO 3 — Final"), Some("Final".to_string()));
+    }
+
+    #[test]
+    fn chapter_heading_without_title_is_empty() {
+        assert_eq!(chapter_heading("Capítulo 2"), Some(String::new()));
+        assert_eq!(chapter_heading("Chapter 7:"), Some(String::new()));
+    }
+
+    #[test]
+    fn chapter_heading_rejects_other_lines() {
+        assert_eq!(chapter_heading("Capítulos 1 a 3 foram revisados"), None);
+        assert_eq!(chapter_heading("Capítulo um"), None);
+        assert_eq!(chapter_heading("Capítulo"), None);
+        assert_eq!(chapter_heading("O capítulo 1 começa aqui"), None);
+        assert_eq!(chapter_heading(""), None);
+    }
+
+    #[test]
+    fn content_numbers_chapters_and_keeps_introduction_untitled() {
+        let book = ImportedBook {
+            chapters: vec![
+                ImportedChapter { title: None, content: "Prefácio.\n".to_string() },
+                ImportedChapter { title: Some("Início".to_string()), content: "Texto um.\n\n".to_string() },
+                ImportedChapter { title: None, content: "Texto dois.".to_string() },
+            ],
+            ..ImportedBook::default()
+        };
+        assert_eq!(
+            book.content(),
+            "Prefácio.\n\nCapítulo 1: Início\n\nTexto um.\n\nCapítulo 2:\n\nTexto dois."
+        );
+    }
+
+    #[test]
+    fn read_entry_limits_total_uncompressed_size() {
+        let bytes = zip_package(&[("a.txt", "12345"), ("b.txt", "67890")]);
+        let mut archive = open_archive(&bytes).unwrap();
+        archive.remaining_bytes = 8;
+
+        assert_eq!(read_entry(&mut archive, "a.txt").unwrap().as_deref(), Some("12345"));
+        assert!(read_entry(&mut archive, "b.txt").is_err());
+        assert!(read_entry(&mut archive, "c.txt").unwrap().is_none());
+    }
+
+    #[test]
+    fn open_archive_rejects_non_zip() {
+        assert!(open_archive(b"not a zip").is_err());
+    }
+}
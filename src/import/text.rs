@@ -0,0 +1,196 @@
+use crate::import::{decode_text, split_text_chapters, ImportedBook, ImportedChapter};
+
+// Chave de metadados reconhecida no cabeçalho do arquivo
+fn metadata_key(key: &str) -> Option<&'static str> {
+    match key.trim().to_lowercase().as_str() {
+        "title" | "título" | "titulo" => Some("title"),
+        "author" | "autor" | "autora" => Some("author"),
+        "description" | "descrição" | "descricao" => Some("description"),
+        "isbn" => Some("isbn"),
+        _ => None,
+    }
+}
+
+fn set_metadata(book: &mut ImportedBook, key: &str, value: &str) {
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+    match key {
+        "title" => book.title = Some(value),
+        "author" => book.author = Some(value),
+        "description" => book.description = Some(value),
+        "isbn" => book.isbn = Some(value),
+        _ => {}
+    }
+}
+
+// Texto puro: linhas "Título: ..." e "Autor: ..." no início do arquivo são
+// metadados; capítulos seguem o padrão "Capítulo N: título"
+pub fn parse_text(bytes: &[u8]) -> ImportedBook {
+    let mut book = ImportedBook::default();
+    let text = decode_text(bytes, &mut book);
+
+    let mut body_start = 0;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            body_start += line.len() + 1;
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else { break };
+        let Some(key) = metadata_key(key) else { break };
+        set_metadata(&mut book, key, value);
+        body_start += line.len() + 1;
+    }
+
+    book.chapters = split_text_chapters(&text[body_start.min(text.len())..]);
+    book
+}
+
+// Nível do título Markdown no estilo ATX ("# Título"), com o texto
+fn atx_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim().to_string()))
+}
+
+// Markdown: front matter YAML simples (title, author...) e capítulos nos
+// títulos "#". Com um único "#" no topo seguido de "##", o "#" é o título do
+// livro e os capítulos são os "##".
+pub fn parse_markdown(bytes: &[u8]) -> ImportedBook {
+    let mut book = ImportedBook::default();
+    let text = decode_text(bytes, &mut book);
+    let mut body = text.as_str();
+
+    if let Some(rest) = body.strip_prefix("---\n") {
+        if let Some(end) = rest.find("\n---") {
+            for line in rest[..end].lines() {
+                if let Some((key, value)) = line.split_once(':') {
+                    if let Some(key) = metadata_key(key) {
+                        set_metadata(&mut book, key, value);
+                    }
+                }
+            }
+            body = rest[end + 4..].trim_start_matches(|c| c != '\n').trim_start_matches('\n');
+        }
+    }
+
+    // Títulos fora de blocos de código, com o número da linha
+    let mut headings = Vec::new();
+    let mut in_code = false;
+    for (i, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_code = !in_code;
+        } else if !in_code {
+            if let Some((level, title)) = atx_heading(line) {
+                headings.push((i, level, title));
+            }
+        }
+    }
+
+    let top_level = headings.iter().filter(|(_, level, _)| *level == 1).count();
+    let (title_line, chapter_level) = match headings.first() {
+        Some((line, 1, title)) if top_level == 1 && headings.iter().any(|(_, level, _)| *level == 2) => {
+            if book.title.is_none() {
+                book.title = Some(title.clone());
+            }
+            (Some(*line), 2)
+        }
+        Some(_) => (None, headings.iter().map(|(_, level, _)| *level).min().unwrap_or(1)),
+        None => {
+            book.chapters = split_text_chapters(body);
+            return book;
+        }
+    };
+
+    let mut chapters = vec![ImportedChapter::default()];
+    let mut in_code = false;
+    for (i, line) in body.lines().enumerate() {
+        if Some(i) == title_line {
+            continue;
+        }
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_code = !in_code;
+        }
+        match atx_heading(line).filter(|(level, _)| !in_code && *level == chapter_level) {
+            Some((_, title)) => chapters.push(ImportedChapter { title: Some(title), content: String::new() }),
+            None => {
+                if let Some(current) = chapters.last_mut() {
+                    current.content.push_str(line);
+                    current.content.push('\n');
+                }
+            }
+        }
+    }
+    book.chapters = chapters;
+    book
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(book: &ImportedBook) -> Vec<Option<&str>> {
+        book.chapters.iter().map(|c| c.title.as_deref()).collect()
+    }
+
+    #[test]
+    fn text_reads_metadata_header_and_chapters() {
+        let book = parse_text("Título: Meu Livro\nAutor: Ana\n\nPrefácio.\nCapítulo 1: Início\nUm.\nCapítulo 2\nDois.\n".as_bytes());
+        assert_eq!(book.title.as_deref(), Some("Meu Livro"));
+        assert_eq!(book.author.as_deref(), Some("Ana"));
+        assert_eq!(titles(&book), vec![None, Some("Início"), Some("")]);
+        assert_eq!(book.chapters[0].content, "Prefácio.\n");
+        assert_eq!(book.chapters[2].content, "Dois.\n");
+    }
+
+    #[test]
+    fn text_header_stops_at_first_non_metadata_line() {
+        let book = parse_text("Nota: não é metadado\nTítulo: Também não\n".as_bytes());
+        assert!(book.title.is_none());
+        assert_eq!(book.chapters[0].content, "Nota: não é metadado\nTítulo: Também não\n");
+    }
+
+    #[test]
+    fn text_falls_back_to_latin1() {
+        let book = parse_text(b"Cap\xEDtulo 1: A\nol\xE1\n");
+        assert_eq!(titles(&book), vec![None, Some("A")]);
+        assert_eq!(book.chapters[1].content, "olá\n");
+        assert_eq!(book.warnings[0].code, "ENCODING_FALLBACK");
+    }
+
+    #[test]
+    fn markdown_single_top_heading_is_book_title() {
+        let book = parse_markdown("# O Livro\n\n## Um\nTexto.\n\n### Seção\nMais.\n\n## Dois\nFim.\n".as_bytes());
+        assert_eq!(book.title.as_deref(), Some("O Livro"));
+        assert_eq!(titles(&book), vec![None, Some("Um"), Some("Dois")]);
+        assert_eq!(book.chapters[1].content, "Texto.\n\n### Seção\nMais.\n\n");
+    }
+
+    #[test]
+    fn markdown_front_matter_wins_over_heading() {
+        let book = parse_markdown("---\ntitle: \"Do Cabeçalho\"\nautor: Bia\n---\n# Título\n## Um\nx\n".as_bytes());
+        assert_eq!(book.title.as_deref(), Some("Do Cabeçalho"));
+        assert_eq!(book.author.as_deref(), Some("Bia"));
+        assert_eq!(titles(&book), vec![None, Some("Um")]);
+    }
+
+    #[test]
+    fn markdown_splits_on_highest_level_and_ignores_code_blocks() {
+        let book = parse_markdown("Intro\n# Um #\n```\n# comentário\n```\n## Sub\n# Dois\nfim\n".as_bytes());
+        assert!(book.title.is_none());
+        assert_eq!(titles(&book), vec![None, Some("Um"), Some("Dois")]);
+        assert_eq!(book.chapters[1].content, "```\n# comentário\n```\n## Sub\n");
+    }
+
+    #[test]
+    fn markdown_without_headings_uses_chapter_lines() {
+        let book = parse_markdown("Capítulo 1: A\n*a*\n#hashtag\n".as_bytes());
+        assert_eq!(titles(&book), vec![None, Some("A")]);
+        assert_eq!(book.chapters[1].content, "*a*\n#hashtag\n");
+    }
+}
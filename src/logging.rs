@@ -0,0 +1,47 @@
+use chrono::Utc;
+use std::fmt::Arguments;
+use crate::config::{try_settings, LogFormat, LogLevel};
+
+// Escrever uma linha de log respeitando o nível e o formato configurados.
+// Erros e avisos vão para stderr; o restante para stdout.
+pub fn write(level: LogLevel, message: Arguments) {
+    let (min_level, format) = match try_settings() {
+        Some(settings) => (settings.logging.level, settings.logging.format),
+        None => (LogLevel::Info, LogFormat::Text),
+    };
+
+    if level > min_level {
+        return;
+    }
+
+    let line = match format {
+        LogFormat::Json => serde_json::json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "level": level,
+            "message": message.to_string(),
+        })
+        .to_string(),
+        LogFormat::Text => format!("{} {:<5} {}", Utc::now().format("%Y-%m-%d %H:%M:%S"), level.as_str(), message),
+    };
+
+    if level <= LogLevel::Warn {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::logging::write($crate::config::LogLevel::Error, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::logging::write($crate::config::LogLevel::Warn, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::logging::write($crate::config::LogLevel::Info, format_args!($($arg)*)) };
+}
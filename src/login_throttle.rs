@@ -0,0 +1,342 @@
+// Proteção do login contra tentativas de senha em massa: espera exponencial
+// entre tentativas (por email e por IP) e bloqueio temporário da conta
+use std::net::IpAddr;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+use crate::{
+    config::{settings, LoginSettings},
+    database::get_pool,
+    errors::AppError,
+};
+
+// Emails maiores que a coluna de usuários não existem no cadastro
+const MAX_EMAIL_LEN: usize = 255;
+
+#[derive(sqlx::FromRow)]
+struct Throttle {
+    key: String,
+    failures: i32,
+    last_failed_at: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+fn normalized_email(email: &str) -> String {
+    email.trim().to_lowercase().chars().take(MAX_EMAIL_LEN).collect()
+}
+
+fn email_key(email: &str) -> String {
+    format!("email:{}", normalized_email(email))
+}
+
+fn ip_key(ip: IpAddr) -> String {
+    format!("ip:{}", ip)
+}
+
+fn keys(email: &str, ip: Option<IpAddr>) -> Vec<String> {
+    [Some(email_key(email)), ip.map(ip_key)].into_iter().flatten().collect()
+}
+
+// Segundos (arredondados para cima) até o instante informado
+fn seconds_until(instant: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    ((instant - now).num_milliseconds() + 999) / 1000
+}
+
+// Espera exigida depois de `failures` falhas seguidas: começa em `after` falhas
+// e dobra a cada nova falha, até o máximo configurado
+fn backoff_seconds(config: &LoginSettings, failures: i32, after: i32) -> i64 {
+    if failures < after {
+        return 0;
+    }
+    let doublings = (failures - after).min(32) as u32;
+    config
+        .backoff_base_seconds
+        .saturating_mul(1i64 << doublings)
+        .min(config.backoff_max_seconds)
+}
+
+// Fim do bloqueio da conta, se ainda estiver em vigor
+fn active_lockout(throttles: &[Throttle], now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    throttles.iter().filter_map(|t| t.locked_until).find(|until| *until > now)
+}
+
+// Quando a próxima tentativa será aceita: a maior espera entre as falhas
+// (do email e do IP) ainda dentro da janela
+fn next_attempt_at(config: &LoginSettings, throttles: &[Throttle], now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let window = Duration::minutes(config.failure_window_minutes);
+    throttles
+        .iter()
+        .filter(|t| t.last_failed_at + window > now)
+        .map(|t| {
+            let after = if t.key.starts_with("ip:") {
+                config.ip_backoff_after_failures
+            } else {
+                config.backoff_after_failures
+            };
+            t.last_failed_at + Duration::seconds(backoff_seconds(config, t.failures, after))
+        })
+        .max()
+}
+
+// Recusar a tentativa se a conta estiver bloqueada ou se a espera desde a
+// última falha (do email ou do IP) ainda não terminou
+pub async fn check(email: &str, ip: Option<IpAddr>) -> Result<(), AppError> {
+    let pool = get_pool();
+    let config = &settings().login;
+
+    let throttles = match sqlx::query_as::<_, Throttle>(
+        "SELECT key, failures, last_failed_at, locked_until FROM login_throttles WHERE key = ANY($1)"
+    )
+    .bind(keys(email, ip))
+    .fetch_all(pool)
+    .await
+    {
+        Ok(throttles) => throttles,
+        Err(e) => return Err(AppError::internal("Erro ao verificar tentativas de login", e)),
+    };
+
+    let now = Utc::now();
+    if let Some(locked_until) = active_lockout(&throttles, now) {
+        let seconds = seconds_until(locked_until, now);
+        return Err(AppError::too_many_requests(
+            "ACCOUNT_LOCKED",
+            format!(
+                "Conta bloqueada temporariamente por excesso de tentativas; tente novamente em {} minutos",
+                (seconds + 59) / 60
+            ),
+            seconds,
+        ));
+    }
+
+    match next_attempt_at(config, &throttles, now) {
+        Some(ready_at) if ready_at > now => {
+            let seconds = seconds_until(ready_at, now);
+            Err(AppError::too_many_requests(
+                "LOGIN_THROTTLED",
+                format!("Muitas tentativas de login; aguarde {} segundos", seconds),
+                seconds,
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Registrar uma tentativa que falhou. Ao atingir o limite, a conta é
+// bloqueada e o evento fica disponível para os administradores.
+pub async fn record_failure(email: &str, ip: Option<IpAddr>, user_id: Option<Uuid>) -> Result<(), AppError> {
+    let pool = get_pool();
+    let config = &settings().login;
+
+    // Sem falhas dentro da janela, a contagem recomeça
+    for key in keys(email, ip) {
+        if let Err(e) = sqlx::query(
+            r#"
+            INSERT INTO login_throttles (key, failures, last_failed_at) VALUES ($1, 1, NOW())
+            ON CONFLICT (key) DO UPDATE SET
+                failures = CASE
+                    WHEN login_throttles.last_failed_at <= NOW() - make_interval(mins => $2::int) THEN 1
+                    ELSE login_throttles.failures + 1
+                END,
+                last_failed_at = NOW()
+            "#
+        )
+        .bind(&key)
+        .bind(config.failure_window_minutes)
+        .execute(pool)
+        .await
+        {
+            return Err(AppError::internal("Erro ao registrar tentativa de login", e));
+        }
+    }
+
+    // Bloqueio: apenas uma requisição concorrente encontra a contagem no limite
+    let email = normalized_email(email);
+    let locked_until = match sqlx::query_scalar::<_, DateTime<Utc>>(
+        r#"
+        UPDATE login_throttles SET failures = 0, locked_until = NOW() + make_interval(mins => $2::int)
+        WHERE key = $1 AND failures >= $3
+        RETURNING locked_until
+        "#
+    )
+    .bind(email_key(&email))
+    .bind(config.lockout_minutes)
+    .bind(config.lockout_after_failures)
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(locked_until)) => locked_until,
+        Ok(None) => return Ok(()),
+        Err(e) => return Err(AppError::internal("Erro ao bloquear conta", e)),
+    };
+
+    log_warn!("Conta {} bloqueada até {} por excesso de tentativas de login", email, locked_until);
+    match sqlx::query(
+        "INSERT INTO account_lockout_events (email, user_id, event, locked_until, ip_address) VALUES ($1, $2, 'locked', $3, $4)"
+    )
+    .bind(&email)
+    .bind(user_id)
+    .bind(locked_until)
+    .bind(ip.map(|ip| ip.to_string()))
+    .execute(pool)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(AppError::internal("Erro ao registrar bloqueio de conta", e)),
+    }
+}
+
+// Login concluído: zerar as falhas do email (as do IP expiram com a janela)
+pub async fn clear(email: &str) -> Result<(), AppError> {
+    let pool = get_pool();
+
+    match sqlx::query("DELETE FROM login_throttles WHERE key = $1")
+        .bind(email_key(email))
+        .execute(pool)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(AppError::internal("Erro ao limpar tentativas de login", e)),
+    }
+}
+
+// Apagar as falhas do email e, se a conta estava bloqueada, registrar o
+// desbloqueio feito por `actor_id`. Devolve se a conta estava bloqueada.
+async fn release(conn: &mut PgConnection, email: &str, user_id: Uuid, actor_id: Uuid) -> Result<bool, sqlx::Error> {
+    let was_locked = sqlx::query_scalar::<_, bool>(
+        "DELETE FROM login_throttles WHERE key = $1 RETURNING COALESCE(locked_until > NOW(), FALSE)"
+    )
+    .bind(email_key(email))
+    .fetch_optional(&mut *conn)
+    .await?
+    .unwrap_or(false);
+
+    if was_locked {
+        sqlx::query(
+            "INSERT INTO account_lockout_events (email, user_id, event, actor_id) VALUES ($1, $2, 'unlocked', $3)"
+        )
+        .bind(normalized_email(email))
+        .bind(user_id)
+        .bind(actor_id)
+        .execute(conn)
+        .await?;
+    }
+    Ok(was_locked)
+}
+
+// Desbloqueio manual por um administrador. A contagem de falhas é zerada de
+// qualquer forma; devolve se a conta estava bloqueada.
+pub async fn unlock(email: &str, user_id: Uuid, admin_id: Uuid) -> Result<bool, AppError> {
+    let pool = get_pool();
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+    let was_locked = match release(&mut tx, email, user_id, admin_id).await {
+        Ok(was_locked) => was_locked,
+        Err(e) => return Err(AppError::internal("Erro ao desbloquear conta", e)),
+    };
+    tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar desbloqueio da conta", e))?;
+
+    if was_locked {
+        log_info!("Conta {} desbloqueada pelo administrador {}", normalized_email(email), admin_id);
+    }
+    Ok(was_locked)
+}
+
+// Senha redefinida pelo link do email: a conta deixa de estar bloqueada.
+// Roda na transação da troca de senha; o próprio usuário fica como responsável.
+pub async fn unlock_after_reset(conn: &mut PgConnection, email: &str, user_id: Uuid) -> Result<(), sqlx::Error> {
+    if release(conn, email, user_id, user_id).await? {
+        log_info!("Conta {} desbloqueada pela redefinição de senha", normalized_email(email));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LoginSettings {
+        LoginSettings {
+            backoff_after_failures: 3,
+            backoff_base_seconds: 1,
+            backoff_max_seconds: 300,
+            lockout_after_failures: 10,
+            lockout_minutes: 15,
+            ip_backoff_after_failures: 20,
+            failure_window_minutes: 15,
+        }
+    }
+
+    fn throttle(key: &str, failures: i32, last_failed_at: DateTime<Utc>, locked_until: Option<DateTime<Utc>>) -> Throttle {
+        Throttle { key: key.to_string(), failures, last_failed_at, locked_until }
+    }
+
+    #[test]
+    fn backoff_starts_at_threshold_and_doubles() {
+        let config = config();
+        assert_eq!(backoff_seconds(&config, 0, 3), 0);
+        assert_eq!(backoff_seconds(&config, 2, 3), 0);
+        assert_eq!(backoff_seconds(&config, 3, 3), 1);
+        assert_eq!(backoff_seconds(&config, 4, 3), 2);
+        assert_eq!(backoff_seconds(&config, 7, 3), 16);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let config = config();
+        assert_eq!(backoff_seconds(&config, 11, 3), 256);
+        assert_eq!(backoff_seconds(&config, 12, 3), 300);
+        assert_eq!(backoff_seconds(&config, 200, 3), 300);
+        assert_eq!(backoff_seconds(&config, i32::MAX, 3), 300);
+
+        let config = LoginSettings { backoff_base_seconds: i64::MAX / 2, backoff_max_seconds: i64::MAX, ..config };
+        assert_eq!(backoff_seconds(&config, 40, 3), i64::MAX);
+    }
+
+    #[test]
+    fn ip_failures_use_their_own_threshold() {
+        let config = config();
+        let now = Utc::now();
+        let ip = [throttle("ip:127.0.0.1", 10, now, None)];
+        assert_eq!(next_attempt_at(&config, &ip, now), Some(now));
+
+        let ip = [throttle("ip:127.0.0.1", 22, now, None)];
+        assert_eq!(next_attempt_at(&config, &ip, now), Some(now + Duration::seconds(4)));
+    }
+
+    #[test]
+    fn wait_uses_the_longest_backoff_within_window() {
+        let config = config();
+        let now = Utc::now();
+        let throttles = [
+            throttle("email:a@b.c", 5, now - Duration::seconds(1), None),
+            throttle("ip:127.0.0.1", 21, now, None),
+        ];
+        assert_eq!(next_attempt_at(&config, &throttles, now), Some(now + Duration::seconds(3)));
+
+        // Falhas fora da janela não contam mais
+        let old = [throttle("email:a@b.c", 9, now - Duration::minutes(16), None)];
+        assert_eq!(next_attempt_at(&config, &old, now), None);
+    }
+
+    #[test]
+    fn lockout_expires_at_locked_until() {
+        let now = Utc::now();
+        let until = now + Duration::minutes(15);
+        let locked = [throttle("email:a@b.c", 0, now, Some(until))];
+        assert_eq!(active_lockout(&locked, now), Some(until));
+        assert_eq!(active_lockout(&locked, until - Duration::milliseconds(1)), Some(until));
+        assert_eq!(active_lockout(&locked, until), None);
+        assert_eq!(active_lockout(&locked, until + Duration::minutes(1)), None);
+
+        let unlocked = [throttle("email:a@b.c", 3, now, None)];
+        assert_eq!(active_lockout(&unlocked, now), None);
+    }
+
+    #[test]
+    fn seconds_until_rounds_up() {
+        let now = Utc::now();
+        assert_eq!(seconds_until(now + Duration::milliseconds(1), now), 1);
+        assert_eq!(seconds_until(now + Duration::seconds(2), now), 2);
+        assert_eq!(seconds_until(now + Duration::milliseconds(2001), now), 3);
+    }
+}
@@ -0,0 +1,135 @@
+// Envio de emails: transporte em arquivo (desenvolvimento e testes) ou SMTP
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use anyhow::Result;
+use chrono::Utc;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use uuid::Uuid;
+use crate::config::{MailSettings, MailTransport, SmtpTls};
+
+// Transporte configurado na inicialização
+static MAILER: OnceLock<Box<dyn Mailer>> = OnceLock::new();
+
+// Email em texto puro para um destinatário
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to_name: String,
+    pub to_address: String,
+    pub subject: String,
+    pub body: String,
+}
+
+// Falha ao montar ou entregar um email
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[rocket::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), MailError>;
+}
+
+fn build_message(from: &Mailbox, email: &Email) -> Result<Message, MailError> {
+    let address = email
+        .to_address
+        .parse()
+        .map_err(|e| MailError(format!("destinatário inválido ({})", e)))?;
+    Message::builder()
+        .from(from.clone())
+        .to(Mailbox::new(Some(email.to_name.clone()), address))
+        .subject(&email.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())
+        .map_err(|e| MailError(format!("erro ao montar email ({})", e)))
+}
+
+// Grava cada email como um arquivo .eml no diretório configurado e registra no log
+pub struct FileMailer {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+#[rocket::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let message = build_message(&self.from, email)?;
+        let path = self
+            .dir
+            .join(format!("{}-{}.eml", Utc::now().format("%Y%m%d%H%M%S"), Uuid::new_v4()));
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| MailError(format!("erro ao criar {} ({})", self.dir.display(), e)))?;
+        tokio::fs::write(&path, message.formatted())
+            .await
+            .map_err(|e| MailError(format!("erro ao gravar {} ({})", path.display(), e)))?;
+
+        log_info!("Email \"{}\" para {} gravado em {}", email.subject, email.to_address, path.display());
+        Ok(())
+    }
+}
+
+// Envia pelo servidor SMTP configurado
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+#[rocket::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let message = build_message(&self.from, email)?;
+        match self.transport.send(message).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(MailError(format!("erro ao enviar email para {} ({})", email.to_address, e))),
+        }
+    }
+}
+
+// Criar o transporte da seção [mail]
+pub fn init_mailer(config: &MailSettings) -> Result<()> {
+    let from: Mailbox = config.from.parse()?;
+
+    let mailer: Box<dyn Mailer> = match config.transport {
+        MailTransport::File => Box::new(FileMailer { from, dir: PathBuf::from(&config.outbox_dir) }),
+        MailTransport::Smtp => {
+            let mut builder = match config.smtp_tls {
+                SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_host),
+                SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?,
+                SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)?,
+            }
+            .port(config.smtp_port);
+            if !config.smtp_username.is_empty() {
+                builder = builder.credentials(Credentials::new(
+                    config.smtp_username.clone(),
+                    config.smtp_password.clone(),
+                ));
+            }
+            Box::new(SmtpMailer { from, transport: builder.build() })
+        }
+    };
+
+    if MAILER.set(mailer).is_err() {
+        anyhow::bail!("Transporte de email já foi inicializado");
+    }
+    Ok(())
+}
+
+pub fn get_mailer() -> &'static dyn Mailer {
+    MAILER.get().expect("Transporte de email não inicializado").as_ref()
+}
+
+// Link absoluto para uma página da aplicação
+pub fn app_link(path: &str) -> String {
+    let base = &crate::config::settings().mail.app_url;
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
@@ -1,10 +1,26 @@
-use rocket::{get, launch, routes, Build, Rocket};
+use rocket::{catchers, fairing::AdHoc, get, routes, Build, Rocket, Config};
 use rocket_dyn_templates::{context, Template};
+use figment::providers::Env;
 
+#[macro_use]
+mod logging;
+mod config;
+mod errors;
+mod export;
+mod render;
 mod database;
 mod models;
 mod handlers;
+mod import;
+mod guards;
+mod login_throttle;
+mod mailer;
+mod scheduler;
+mod tokens;
+mod two_factor;
+mod validation;
 
+use config::{init_settings, DatabaseSettings, LogFormat, Settings};
 use database::init_db;
 
 #[get("/")]
@@ -29,6 +45,22 @@ fn register_page() -> Template {
     })
 }
 
+// Pedir o link (sem token) ou escolher a nova senha (com o token do email)
+#[get("/reset-password?<token>")]
+fn reset_password_page(token: Option<String>) -> Template {
+    Template::render("reset_password", context! {
+        title: "Redefinir senha - Rocket + PostgreSQL",
+        token
+    })
+}
+
+#[get("/verify-email")]
+fn verify_email_page() -> Template {
+    Template::render("verify_email", context! {
+        title: "Confirmar email - Rocket + PostgreSQL"
+    })
+}
+
 #[get("/dashboard")]
 fn dashboard_page() -> Template {
     Template::render("dashboard", context! {
@@ -43,36 +75,209 @@ fn books_page() -> Template {
     })
 }
 
-#[launch]
-fn rocket() -> Rocket<Build> {
+#[rocket::main]
+async fn main() {
+    // Carregar e validar configurações (project.toml + variáveis de ambiente)
+    let settings = init_settings().expect("Configuração inválida");
+
+    // Subcomando de migrações: `migrate up`, `migrate down [N]` ou `migrate status`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        if let Err(e) = run_migrate_command(&settings.database, &args[1..]).await {
+            log_error!("Erro ao executar migrações: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Primeiro administrador: `create-admin <email> <nome>`, senha em ADMIN_PASSWORD
+    if args.first().map(String::as_str) == Some("create-admin") {
+        if let Err(e) = run_create_admin_command(&settings.database, &args[1..]).await {
+            log_error!("Erro ao criar administrador: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if settings.uses_example_secret() {
+        log_warn!("security.jwt_secret usa o valor de exemplo; defina JWT_SECRET em produção");
+    }
+
     // Inicializar banco de dados
-    tokio::runtime::Runtime::new()
-        .unwrap()
-        .block_on(init_db())
+    init_db(&settings.database)
+        .await
         .expect("Falha ao inicializar banco de dados");
 
-    rocket::build()
+    mailer::init_mailer(&settings.mail).expect("Falha ao configurar envio de emails");
+
+    if let Err(e) = rocket(settings).launch().await {
+        log_error!("Falha ao executar o servidor: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// Executar o subcomando `migrate`
+async fn run_migrate_command(config: &DatabaseSettings, args: &[String]) -> anyhow::Result<()> {
+    let pool = database::connect(config).await?;
+
+    match args.first().map(String::as_str).unwrap_or("status") {
+        "up" => database::migrate_up(&pool).await?,
+        "down" => {
+            let steps = match args.get(1) {
+                Some(steps) => steps.parse()?,
+                None => 1,
+            };
+            database::migrate_down(&pool, steps).await?;
+        }
+        "status" => {
+            for migration in database::migration_status(&pool).await? {
+                let state = match (migration.installed_on, migration.checksum_ok) {
+                    (Some(_), false) => "alterada".to_string(),
+                    (Some(installed_on), true) => format!("aplicada em {}", installed_on.format("%Y-%m-%d %H:%M:%S")),
+                    (None, _) => "pendente".to_string(),
+                };
+                println!("{:04} {:<30} {}", migration.version, migration.description, state);
+            }
+        }
+        other => anyhow::bail!("subcomando desconhecido '{}' (use up, down [N] ou status)", other),
+    }
+
+    Ok(())
+}
+
+// Executar o subcomando `create-admin`: aplica as migrações e cria o usuário
+// como administrador, ou promove a conta que já usa o email (definindo a senha)
+async fn run_create_admin_command(config: &DatabaseSettings, args: &[String]) -> anyhow::Result<()> {
+    let (email, name) = match args {
+        [email, name] => (email.trim(), name.trim()),
+        _ => anyhow::bail!("uso: create-admin <email> <nome> (senha na variável ADMIN_PASSWORD)"),
+    };
+    anyhow::ensure!(email.contains('@'), "email inválido '{}'", email);
+    validation::not_blank(name).map_err(|_| anyhow::anyhow!("o nome não pode ficar em branco"))?;
+
+    let password = std::env::var("ADMIN_PASSWORD")
+        .map_err(|_| anyhow::anyhow!("defina a senha na variável ADMIN_PASSWORD"))?;
+    if let Err(e) = validation::password(&password) {
+        anyhow::bail!("{}", e.message.unwrap_or_default());
+    }
+    let password_hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST)?;
+
+    let pool = database::connect(config).await?;
+    database::migrate_up(&pool).await?;
+
+    // O email do administrador fica confirmado: a conta é criada pela linha de comando
+    let created: bool = sqlx::query_scalar(
+        r#"
+        INSERT INTO users (name, email, password_hash, role, email_verified_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (email) DO UPDATE SET
+            password_hash = EXCLUDED.password_hash,
+            role = EXCLUDED.role,
+            email_verified_at = COALESCE(users.email_verified_at, NOW()),
+            updated_at = NOW()
+        RETURNING xmax = 0
+        "#
+    )
+    .bind(name)
+    .bind(email)
+    .bind(&password_hash)
+    .bind(models::Role::Admin)
+    .fetch_one(&pool)
+    .await?;
+
+    if created {
+        log_info!("Administrador {} criado", email);
+    } else {
+        log_info!("Usuário {} promovido a administrador", email);
+    }
+    Ok(())
+}
+
+fn rocket(settings: &Settings) -> Rocket<Build> {
+    let publish_interval = std::time::Duration::from_secs(settings.jobs.publish_interval_seconds);
+
+    // Configuração do servidor; variáveis ROCKET_* continuam tendo prioridade
+    let figment = Config::figment()
+        .merge(("address", &settings.server.host))
+        .merge(("port", settings.server.port))
+        .merge(("workers", settings.server.workers))
+        .merge(("limits.file", format!("{} MiB", settings.server.max_upload_mb)))
+        .merge(("limits.data-form", format!("{} MiB", settings.server.max_upload_mb + 1)))
+        .merge(("log_level", settings.logging.level.rocket_level()))
+        .merge(("cli_colors", settings.logging.format == LogFormat::Text))
+        .merge(Env::prefixed("ROCKET_").ignore(&["PROFILE"]).global());
+
+    rocket::custom(figment)
         .mount("/", routes![
             index,
             login_page,
             register_page,
+            reset_password_page,
+            verify_email_page,
             dashboard_page,
             books_page,
             handlers::users::get_users,
+            handlers::users::get_lockout_events,
+            handlers::users::unlock_user,
             handlers::users::get_user,
             handlers::users::create_user,
             handlers::users::update_user,
+            handlers::users::update_user_role,
             handlers::users::delete_user,
             handlers::auth::login,
+            handlers::auth::login_mfa,
             handlers::auth::register,
+            handlers::auth::refresh,
+            handlers::auth::logout,
+            handlers::auth::logout_all,
             handlers::auth::verify_token,
+            handlers::auth::forgot_password,
+            handlers::auth::reset_password,
+            handlers::auth::verify_email,
             handlers::books::get_books,
+            handlers::books::search_books,
             handlers::books::get_book,
+            handlers::export::get_book_html,
             handlers::books::create_book,
+            handlers::import::import_book,
             handlers::books::update_book,
             handlers::books::delete_book,
+            handlers::books::update_book_status,
             handlers::books::get_categories,
-            handlers::books::create_category
+            handlers::books::create_category,
+            handlers::chapters::get_chapters,
+            handlers::chapters::get_chapter,
+            handlers::chapters::create_chapter,
+            handlers::chapters::update_chapter,
+            handlers::chapters::reorder_chapters,
+            handlers::chapters::delete_chapter,
+            handlers::revisions::get_revisions,
+            handlers::revisions::diff_revisions,
+            handlers::revisions::get_revision,
+            handlers::revisions::restore_revision,
+            handlers::export::export_epub,
+            handlers::export::export_pdf,
+            handlers::me::get_me,
+            handlers::me::update_me,
+            handlers::me::change_password,
+            handlers::me::resend_verification_email,
+            handlers::two_factor::setup_two_factor,
+            handlers::two_factor::confirm_two_factor,
+            handlers::two_factor::regenerate_recovery_codes,
+            handlers::two_factor::disable_two_factor,
+            handlers::access_tokens::get_access_tokens,
+            handlers::access_tokens::create_access_token,
+            handlers::access_tokens::revoke_access_token,
+            handlers::me::get_my_books,
+            handlers::progress::get_progress,
+            handlers::progress::update_progress,
+            handlers::progress::continue_reading,
+            handlers::stats::get_my_stats,
+            handlers::stats::get_user_stats
         ])
+        .register("/", catchers![errors::default_catcher])
         .attach(Template::fairing())
+        .attach(AdHoc::on_liftoff("Publicação agendada", move |_| Box::pin(async move {
+            scheduler::spawn_publisher(publish_interval);
+        })))
 }
@@ -3,21 +3,57 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use jsonwebtoken::{encode, decode, Header, Algorithm, Validation, EncodingKey, DecodingKey};
 use chrono::{Duration};
+use validator::Validate;
+use crate::{config::settings, validation};
 
 pub mod book;
 
+// Papel do usuário no sistema (armazenado como texto na coluna users.role)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    #[default]
+    Author,
+    Reader,
+}
+
+impl Role {
+    // Administradores e autores podem criar livros
+    pub fn can_write_books(&self) -> bool {
+        matches!(self, Role::Admin | Role::Author)
+    }
+}
+
 // Modelo de usuário para o banco de dados
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: Uuid,
     pub name: String,
     pub email: String,
+    #[serde(skip_serializing)]
     pub password_hash: String,
     pub age: Option<i32>,
+    pub role: Role,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl User {
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    pub fn has_two_factor(&self) -> bool {
+        self.totp_enabled_at.is_some()
+    }
+}
+
 // Modelo de usuário sem senha para respostas da API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
@@ -25,6 +61,9 @@ pub struct UserResponse {
     pub name: String,
     pub email: String,
     pub age: Option<i32>,
+    pub role: Role,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub two_factor_enabled: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -36,6 +75,9 @@ impl From<User> for UserResponse {
             name: user.name,
             email: user.email,
             age: user.age,
+            role: user.role,
+            email_verified_at: user.email_verified_at,
+            two_factor_enabled: user.totp_enabled_at.is_some(),
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -43,12 +85,17 @@ impl From<User> for UserResponse {
 }
 
 // DTO para criação de usuário (sem campos auto-gerados)
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateUserRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 100, message = "Deve ter no máximo 100 caracteres"))]
     pub name: String,
+    #[validate(email(message = "Email inválido"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub email: String,
+    #[validate(custom(function = "validation::password"))]
     pub password: String,
+    #[validate(range(min = 0, max = 150, message = "Deve estar entre 0 e 150"))]
     pub age: Option<i32>,
+    pub role: Option<Role>,
 }
 
 // DTO para login
@@ -59,21 +106,118 @@ pub struct LoginRequest {
 }
 
 // DTO para registro
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct RegisterRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 100, message = "Deve ter no máximo 100 caracteres"))]
     pub name: String,
+    #[validate(email(message = "Email inválido"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub email: String,
+    #[validate(custom(function = "validation::password"))]
     pub password: String,
+    #[validate(range(min = 0, max = 150, message = "Deve estar entre 0 e 150"))]
     pub age: Option<i32>,
 }
 
+// DTO para pedir o link de redefinição de senha
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Email inválido"))]
+    pub email: String,
+}
+
+// DTO para redefinir a senha com o token recebido por email
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(custom(function = "validation::not_blank"))]
+    pub token: String,
+    #[validate(custom(function = "validation::password"))]
+    pub new_password: String,
+}
+
+// DTO para confirmar o email com o token recebido
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(custom(function = "validation::not_blank"))]
+    pub token: String,
+}
+
 // Resposta de login
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
     pub user: UserResponse,
 }
 
+// Login de conta com 2FA: a sessão só é emitida após o código em /login/mfa
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Resultado do login: sessão emitida ou segunda etapa pendente
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Session(LoginResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
+// DTO para a segunda etapa do login (código TOTP ou de recuperação)
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaLoginRequest {
+    #[validate(custom(function = "validation::not_blank"))]
+    pub mfa_token: String,
+    #[validate(custom(function = "validation::not_blank"))]
+    pub code: String,
+}
+
+// DTO com um código do aplicativo autenticador
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorCodeRequest {
+    #[validate(custom(function = "validation::not_blank"))]
+    pub code: String,
+}
+
+// DTO para desativar o 2FA: senha atual e um código (TOTP ou de recuperação)
+#[derive(Debug, Deserialize, Validate)]
+pub struct DisableTwoFactorRequest {
+    pub password: String,
+    #[validate(custom(function = "validation::not_blank"))]
+    pub code: String,
+}
+
+// Início do cadastro do 2FA: segredo para digitar ou URI para o QR code
+#[derive(Debug, Serialize)]
+pub struct TwoFactorSetupResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+// Códigos de recuperação, exibidos uma única vez
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+// DTO para renovação de sessão e logout
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+// Estado de um refresh token armazenado (apenas o hash SHA-256 é persistido)
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
 // Claims para JWT
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -86,20 +230,23 @@ pub struct Claims {
 // Configuração JWT
 pub struct JwtConfig {
     pub secret: String,
-    pub expiration_hours: i64,
+    pub access_token_minutes: i64,
+    pub refresh_token_days: i64,
 }
 
 impl JwtConfig {
     pub fn new() -> Self {
+        let security = &settings().security;
         Self {
-            secret: "sua_chave_secreta_super_segura_aqui".to_string(),
-            expiration_hours: 24,
+            secret: security.jwt_secret.clone(),
+            access_token_minutes: security.jwt_access_token_minutes,
+            refresh_token_days: security.jwt_refresh_token_days,
         }
     }
 
     pub fn generate_token(&self, user_id: Uuid, email: &str) -> Result<String, jsonwebtoken::errors::Error> {
         let now = Utc::now();
-        let exp = now + Duration::hours(self.expiration_hours);
+        let exp = now + Duration::minutes(self.access_token_minutes);
         
         let claims = Claims {
             sub: user_id.to_string(),
@@ -115,7 +262,6 @@ impl JwtConfig {
         )
     }
 
-    #[allow(dead_code)]
     pub fn validate_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
         let validation = Validation::new(Algorithm::HS256);
         let token_data = decode::<Claims>(
@@ -128,19 +274,148 @@ impl JwtConfig {
 }
 
 // DTO para atualização de usuário (todos os campos opcionais)
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUserRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 100, message = "Deve ter no máximo 100 caracteres"))]
     pub name: Option<String>,
+    #[validate(email(message = "Email inválido"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub email: Option<String>,
+    #[validate(range(min = 0, max = 150, message = "Deve estar entre 0 e 150"))]
     pub age: Option<i32>,
 }
 
+// DTO para alteração de papel (apenas administradores)
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub role: Role,
+}
+
+// Tipo de evento no histórico de bloqueios de conta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum LockoutEventKind {
+    Locked,
+    Unlocked,
+}
+
+// Bloqueio ou desbloqueio de conta por excesso de tentativas de login
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AccountLockoutEvent {
+    pub id: Uuid,
+    pub email: String,
+    pub user_id: Option<Uuid>,
+    pub event: LockoutEventKind,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub ip_address: Option<String>,
+    pub actor_id: Option<Uuid>,
+    // Bloqueio ainda em vigor
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Permissão de um token de acesso pessoal. `admin` inclui as demais e
+// `books:write` inclui `books:read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar")]
+pub enum TokenScope {
+    #[serde(rename = "books:read")]
+    #[sqlx(rename = "books:read")]
+    BooksRead,
+    #[serde(rename = "books:write")]
+    #[sqlx(rename = "books:write")]
+    BooksWrite,
+    #[serde(rename = "admin")]
+    #[sqlx(rename = "admin")]
+    Admin,
+}
+
+// Permissões são gravadas como VARCHAR[]
+impl sqlx::postgres::PgHasArrayType for TokenScope {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_varchar")
+    }
+}
+
+impl TokenScope {
+    pub fn grants(&self, required: TokenScope) -> bool {
+        match self {
+            TokenScope::Admin => true,
+            TokenScope::BooksWrite => required != TokenScope::Admin,
+            TokenScope::BooksRead => required == TokenScope::BooksRead,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::BooksRead => "books:read",
+            TokenScope::BooksWrite => "books:write",
+            TokenScope::Admin => "admin",
+        }
+    }
+}
+
+// Token de acesso pessoal (o valor do token nunca é devolvido depois de criado)
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AccessToken {
+    pub id: Uuid,
+    pub name: String,
+    // Início do token, para reconhecê-lo na listagem
+    pub token_prefix: String,
+    pub scopes: Vec<TokenScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+// DTO para criação de token de acesso pessoal
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateAccessTokenRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 100, message = "Deve ter no máximo 100 caracteres"))]
+    pub name: String,
+    #[validate(length(min = 1, message = "Informe ao menos uma permissão"))]
+    pub scopes: Vec<TokenScope>,
+    // Sem validade informada, o token vale até ser revogado
+    #[validate(range(min = 1, max = 3650, message = "Deve estar entre 1 e 3650"))]
+    pub expires_in_days: Option<i64>,
+}
+
+// Token recém-criado: o valor aparece apenas nesta resposta
+#[derive(Debug, Serialize)]
+pub struct CreatedAccessTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub access_token: AccessToken,
+}
+
+// DTO para troca de senha do usuário autenticado
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    #[validate(custom(function = "validation::password"))]
+    pub new_password: String,
+}
+
 // Resposta padrão da API
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub message: String,
     pub data: Option<T>,
+    // Código de erro legível por máquina (apenas em respostas de erro)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    // Erros por campo (apenas em falhas de validação)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+}
+
+// Erro de validação de um campo da requisição
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
 }
 
 impl<T> ApiResponse<T> {
@@ -149,15 +424,51 @@ impl<T> ApiResponse<T> {
             success: true,
             message: message.to_string(),
             data: Some(data),
+            error_code: None,
+            errors: None,
         }
     }
 
-    #[allow(dead_code)]
-    pub fn error(message: &str) -> Self {
+    pub fn error(code: &str, message: &str) -> Self {
         Self {
             success: false,
             message: message.to_string(),
             data: None,
+            error_code: Some(code.to_string()),
+            errors: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_scope_grants_everything() {
+        for required in [TokenScope::BooksRead, TokenScope::BooksWrite, TokenScope::Admin] {
+            assert!(TokenScope::Admin.grants(required));
+        }
+    }
+
+    #[test]
+    fn books_write_includes_books_read_but_not_admin() {
+        assert!(TokenScope::BooksWrite.grants(TokenScope::BooksRead));
+        assert!(TokenScope::BooksWrite.grants(TokenScope::BooksWrite));
+        assert!(!TokenScope::BooksWrite.grants(TokenScope::Admin));
+    }
+
+    #[test]
+    fn books_read_grants_only_itself() {
+        assert!(TokenScope::BooksRead.grants(TokenScope::BooksRead));
+        assert!(!TokenScope::BooksRead.grants(TokenScope::BooksWrite));
+        assert!(!TokenScope::BooksRead.grants(TokenScope::Admin));
+    }
+
+    #[test]
+    fn scope_names_match_serialization() {
+        for scope in [TokenScope::BooksRead, TokenScope::BooksWrite, TokenScope::Admin] {
+            assert_eq!(serde_json::to_string(&scope).unwrap(), format!("\"{}\"", scope.as_str()));
         }
     }
 }
@@ -1,6 +1,55 @@
+use rocket::{fs::TempFile, FromForm};
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
+use validator::Validate;
+use crate::validation;
+
+// Status editorial do livro (armazenado como texto na coluna books.status)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum BookStatus {
+    #[default]
+    Draft,
+    InReview,
+    Published,
+    Archived,
+}
+
+impl BookStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookStatus::Draft => "draft",
+            BookStatus::InReview => "in_review",
+            BookStatus::Published => "published",
+            BookStatus::Archived => "archived",
+        }
+    }
+
+    // Transições permitidas no fluxo editorial
+    pub fn can_transition_to(&self, next: BookStatus) -> bool {
+        use BookStatus::*;
+        matches!(
+            (self, next),
+            (Draft, InReview)
+                | (InReview, Draft)
+                | (InReview, Published)
+                | (Published, Archived)
+                | (Archived, Draft)
+        )
+    }
+}
+
+// Formato do conteúdo do livro (coluna books.content_format)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum ContentFormat {
+    #[default]
+    Plain,
+    Markdown,
+}
 
 // Modelo de categoria de livro
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -13,6 +62,7 @@ pub struct Category {
 }
 
 // Modelo de livro para o banco de dados
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Book {
     pub id: Uuid,
@@ -21,9 +71,18 @@ pub struct Book {
     pub isbn: Option<String>,
     pub description: Option<String>,
     pub content: String,
+    pub content_format: ContentFormat,
     pub category_id: Uuid,
     pub user_id: Uuid, // Usuário que criou o livro
     pub is_public: bool,
+    pub status: BookStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub scheduled_publish_at: Option<DateTime<Utc>>,
+    // Métricas calculadas pelo banco a partir do conteúdo
+    pub word_count: i32,
+    pub char_count: i32,
+    pub reading_time_minutes: i32,
+    pub page_count: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -37,9 +96,18 @@ pub struct BookWithCategory {
     pub isbn: Option<String>,
     pub description: Option<String>,
     pub content: String,
+    pub content_format: ContentFormat,
     pub category_id: Uuid,
     pub user_id: Uuid,
     pub is_public: bool,
+    pub status: BookStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub scheduled_publish_at: Option<DateTime<Utc>>,
+    // Métricas calculadas pelo banco a partir do conteúdo
+    pub word_count: i32,
+    pub char_count: i32,
+    pub reading_time_minutes: i32,
+    pub page_count: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     // Campos da categoria
@@ -64,13 +132,15 @@ impl BookWithCategory {
 }
 
 // DTO para criação de categoria
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateCategoryRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 100, message = "Deve ter no máximo 100 caracteres"))]
     pub name: String,
     pub description: Option<String>,
 }
 
 // DTO para atualização de categoria
+#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct UpdateCategoryRequest {
     pub name: Option<String>,
@@ -78,36 +148,211 @@ pub struct UpdateCategoryRequest {
 }
 
 // DTO para criação de livro
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateBookRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub title: String,
+    #[validate(custom(function = "validation::not_blank"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub author: String,
+    #[validate(custom(function = "validation::isbn"), length(max = 20, message = "Deve ter no máximo 20 caracteres"))]
     pub isbn: Option<String>,
     pub description: Option<String>,
+    #[validate(custom(function = "validation::not_blank"))]
     pub content: String,
+    #[serde(default)]
+    pub content_format: ContentFormat,
     pub category_id: Uuid,
     pub is_public: bool,
 }
 
 // DTO para atualização de livro
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateBookRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub title: Option<String>,
+    #[validate(custom(function = "validation::not_blank"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub author: Option<String>,
+    #[validate(custom(function = "validation::isbn"), length(max = 20, message = "Deve ter no máximo 20 caracteres"))]
     pub isbn: Option<String>,
     pub description: Option<String>,
+    #[validate(custom(function = "validation::not_blank"))]
     pub content: Option<String>,
+    pub content_format: Option<ContentFormat>,
     pub category_id: Option<Uuid>,
     pub is_public: Option<bool>,
 }
 
-// DTO para busca de livros
-#[derive(Debug, Deserialize)]
+// Capítulo de um livro (a ordem é dada por `position`, começando em 1)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Chapter {
+    pub id: Uuid,
+    pub book_id: Uuid,
+    pub position: i32,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// DTO para criação de capítulo (sem posição, entra no final)
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateChapterRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
+    pub title: String,
+    pub content: String,
+    #[validate(range(min = 1, message = "Deve ser maior que zero"))]
+    pub position: Option<i32>,
+}
+
+// DTO para atualização de capítulo
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateChapterRequest {
+    #[validate(custom(function = "validation::not_blank"), length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
+    pub title: Option<String>,
+    pub content: Option<String>,
+}
+
+// DTO para reordenar capítulos: todos os IDs do livro, na nova ordem
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReorderChaptersRequest {
+    #[validate(length(min = 1, message = "Informe a nova ordem dos capítulos"))]
+    pub chapter_ids: Vec<Uuid>,
+}
+
+// Revisão do conteúdo de um livro (sem o texto, para listagem)
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BookRevisionSummary {
+    pub id: Uuid,
+    pub book_id: Uuid,
+    pub revision_number: i32,
+    pub author_id: Option<Uuid>,
+    pub author_name: Option<String>,
+    pub word_count: i32,
+    pub word_delta: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+// Revisão completa, com o conteúdo salvo
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BookRevision {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub summary: BookRevisionSummary,
+    pub content: String,
+}
+
+// Trecho de um diff entre revisões: igual, inserido ou removido
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffSegment {
+    pub tag: &'static str,
+    pub value: String,
+}
+
+// Diff entre duas revisões
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub mode: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub segments: Vec<DiffSegment>,
+}
+
+// DTO para mudança de status editorial. Com `publish_at` no futuro, a
+// publicação fica agendada e o livro continua em revisão até a data.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateBookStatusRequest {
+    pub status: BookStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+// DTO para busca de livros (parâmetros da query string)
+#[derive(Debug, Deserialize, FromForm, Validate)]
 pub struct BookSearchRequest {
+    #[validate(length(max = 200, message = "Deve ter no máximo 200 caracteres"))]
     pub query: Option<String>,
     pub category_id: Option<String>, // Mudado para String para FromForm
+    #[validate(length(max = 255, message = "Deve ter no máximo 255 caracteres"))]
     pub author: Option<String>,
     pub is_public: Option<bool>,
+    #[validate(range(min = 1, message = "Deve ser maior que zero"))]
+    pub page: Option<i32>,
+    #[validate(range(min = 1, max = 100, message = "Deve estar entre 1 e 100"))]
+    pub per_page: Option<i32>,
+}
+
+// Formulário multipart de importação de manuscrito. Título e autor
+// informados aqui têm prioridade sobre os metadados do arquivo.
+#[derive(Debug, FromForm)]
+pub struct ImportBookForm<'r> {
+    pub file: TempFile<'r>,
+    pub category_id: String,
+    pub is_public: Option<bool>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+}
+
+// Problema não fatal encontrado durante a importação
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportWarning {
+    pub code: &'static str,
+    pub message: String,
+}
+
+// Resultado da importação: livro criado, capítulos detectados e avisos
+#[derive(Debug, Serialize)]
+pub struct ImportBookResponse {
+    pub book: BookWithCategory,
+    pub format: &'static str,
+    pub chapters: i64,
+    pub warnings: Vec<ImportWarning>,
+}
+
+// Conteúdo do livro renderizado em HTML sanitizado, um <section> por capítulo
+#[derive(Debug, Serialize)]
+pub struct RenderedBook {
+    pub book_id: Uuid,
+    pub content_format: ContentFormat,
+    pub html: String,
+}
+
+// Opções da exportação em PDF (query string). Medidas em mm, corpo do texto em pt;
+// `margin` vale para todas as margens que não forem informadas individualmente.
+#[derive(Debug, FromForm, Validate)]
+pub struct PdfExportRequest {
+    #[validate(custom(function = "validation::trim_size"))]
+    pub trim: Option<String>,
+    #[validate(range(min = 80.0, max = 432.0, message = "Deve estar entre 80 e 432 mm"))]
+    pub width: Option<f32>,
+    #[validate(range(min = 80.0, max = 432.0, message = "Deve estar entre 80 e 432 mm"))]
+    pub height: Option<f32>,
+    #[validate(range(min = 5.0, max = 60.0, message = "Deve estar entre 5 e 60 mm"))]
+    pub margin: Option<f32>,
+    #[validate(range(min = 5.0, max = 60.0, message = "Deve estar entre 5 e 60 mm"))]
+    pub margin_top: Option<f32>,
+    #[validate(range(min = 5.0, max = 60.0, message = "Deve estar entre 5 e 60 mm"))]
+    pub margin_bottom: Option<f32>,
+    #[validate(range(min = 5.0, max = 60.0, message = "Deve estar entre 5 e 60 mm"))]
+    pub margin_inner: Option<f32>,
+    #[validate(range(min = 5.0, max = 60.0, message = "Deve estar entre 5 e 60 mm"))]
+    pub margin_outer: Option<f32>,
+    #[validate(range(min = 7.0, max = 18.0, message = "Deve estar entre 7 e 18 pt"))]
+    pub font_size: Option<f32>,
+    #[validate(range(min = 1.0, max = 2.5, message = "Deve estar entre 1 e 2.5"))]
+    pub line_spacing: Option<f32>,
+    pub page_numbers: Option<bool>,
+    pub running_headers: Option<bool>,
+}
+
+// Livro encontrado na busca, com relevância e trecho destacado
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BookSearchResult {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub book: BookWithCategory,
+    pub rank: f32,
+    pub snippet: Option<String>,
 }
 
 // Estatísticas de leitura do usuário
@@ -121,6 +366,22 @@ pub struct ReadingStats {
     pub favorite_category: Option<String>,
 }
 
+// Livros concluídos e páginas lidas em um período (semana ou mês)
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReadingPeriod {
+    pub period_start: NaiveDate,
+    pub books_finished: i64,
+    pub pages_read: i64,
+}
+
+// Estatísticas do usuário com a série histórica para o gráfico do dashboard
+#[derive(Debug, Serialize)]
+pub struct UserStatsResponse {
+    pub stats: ReadingStats,
+    pub weekly: Vec<ReadingPeriod>,
+    pub monthly: Vec<ReadingPeriod>,
+}
+
 // Progresso de leitura de um livro
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ReadingProgress {
@@ -136,16 +397,32 @@ pub struct ReadingProgress {
 }
 
 // DTO para atualizar progresso de leitura
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateProgressRequest {
+    #[validate(range(min = 0, message = "Não pode ser negativo"))]
     pub current_page: i32,
+    // Total de páginas do livro; se omitido, usa a contagem canônica do livro
+    #[validate(range(min = 1, message = "Deve ser maior que zero"))]
+    pub total_pages: Option<i32>,
     pub is_completed: Option<bool>,
 }
 
+// Item da lista "continuar lendo" do usuário
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ContinueReadingItem {
+    pub book_id: Uuid,
+    pub title: String,
+    pub author: String,
+    pub cat_name: String,
+    pub current_page: i32,
+    pub total_pages: i32,
+    pub last_read_at: DateTime<Utc>,
+}
+
 // Resposta de busca de livros
 #[derive(Debug, Serialize)]
 pub struct BookSearchResponse {
-    pub books: Vec<BookWithCategory>,
+    pub books: Vec<BookSearchResult>,
     pub total: i64,
     pub page: i32,
     pub per_page: i32,
@@ -0,0 +1,203 @@
+// Renderização do conteúdo dos livros em HTML seguro para exibição no navegador
+use std::borrow::Cow;
+use std::collections::HashSet;
+use ammonia::Builder;
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+use crate::export::{escape_xml, paragraphs};
+use crate::models::book::ContentFormat;
+
+// Conteúdo de um capítulo em HTML. `id_prefix` torna únicos os ids gerados
+// (notas de rodapé) quando vários capítulos aparecem na mesma página.
+pub fn render_html(content: &str, format: ContentFormat, id_prefix: &str) -> String {
+    match format {
+        ContentFormat::Plain => plain_html(content),
+        ContentFormat::Markdown => sanitizer(id_prefix).clean(&markdown_html(content, true)).to_string(),
+    }
+}
+
+// Conteúdo em XHTML para o EPUB: o mesmo HTML sanitizado, em XML bem formado.
+// As imagens não fazem parte do pacote (e recursos remotos são inválidos no
+// EPUB): do Markdown fica o texto alternativo; <img> em HTML embutido é removido.
+pub fn render_xhtml(content: &str, format: ContentFormat, id_prefix: &str) -> String {
+    let html = match format {
+        ContentFormat::Plain => plain_html(content),
+        ContentFormat::Markdown => {
+            let mut builder = sanitizer(id_prefix);
+            builder.rm_tags(["img"]);
+            builder.clean(&markdown_html(content, false)).to_string()
+        }
+    };
+    html_to_xhtml(&html)
+}
+
+// Conteúdo sem marcação para o PDF: o Markdown vira texto, com parágrafos
+// separados por linhas em branco
+pub fn render_plain_text(content: &str, format: ContentFormat) -> Cow<'_, str> {
+    match format {
+        ContentFormat::Plain => Cow::Borrowed(content),
+        ContentFormat::Markdown => Cow::Owned(markdown_text(content)),
+    }
+}
+
+// Texto puro: parágrafos separados por linhas em branco, quebras de linha preservadas
+fn plain_html(content: &str) -> String {
+    paragraphs(content)
+        .into_iter()
+        .map(|lines| {
+            let lines: Vec<String> = lines.iter().map(|line| escape_xml(line)).collect();
+            format!("<p>{}</p>\n", lines.join("<br>\n"))
+        })
+        .collect()
+}
+
+// Markdown (CommonMark) com tabelas, notas de rodapé e texto riscado
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH
+}
+
+// Sem `images`, as imagens viram o próprio texto alternativo
+fn markdown_html(content: &str, images: bool) -> String {
+    let events = Parser::new_ext(content, markdown_options())
+        .filter(|event| images || !matches!(event, Event::Start(Tag::Image { .. }) | Event::End(TagEnd::Image)));
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+// Texto do Markdown sem a marcação. Cada bloco (parágrafo, título, item de
+// lista, linha de tabela) vira um parágrafo; tags HTML embutidas são descartadas.
+fn markdown_text(content: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(content, markdown_options()) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::FootnoteReference(label) => text.push_str(&format!("[{}]", label)),
+            Event::Start(Tag::Item) => text.push_str("• "),
+            Event::Start(Tag::FootnoteDefinition(label)) => text.push_str(&format!("[{}] ", label)),
+            Event::Start(Tag::List(_)) | Event::Rule => text.push_str("\n\n"),
+            Event::End(TagEnd::TableCell) => text.push_str("  "),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::CodeBlock
+                | TagEnd::Item
+                | TagEnd::TableHead
+                | TagEnd::TableRow
+                | TagEnd::FootnoteDefinition,
+            ) => text.push_str("\n\n"),
+            _ => {}
+        }
+    }
+    text
+}
+
+// Elementos vazios do HTML, que no XML precisam fechar com "/>"
+const VOID_ELEMENTS: &[&str] = &["area", "br", "col", "hr", "img", "wbr"];
+
+// O ammonia serializa em HTML5: tags vazias sem "/>" e espaço não separável
+// como &nbsp;, que não existe em XML. Todo "<" no texto já vem escapado.
+fn html_to_xhtml(html: &str) -> String {
+    let mut xhtml = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        xhtml.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Fim da tag, ignorando ">" dentro de valores de atributo
+        let mut quoted = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                c == '>' && !quoted
+            })
+            .map_or(rest.len(), |(i, _)| i + 1);
+        let tag = &rest[..end];
+        let name: String = tag[1..].chars().take_while(char::is_ascii_alphanumeric).collect();
+
+        if VOID_ELEMENTS.contains(&name.as_str()) && tag.ends_with('>') && !tag.ends_with("/>") {
+            xhtml.push_str(&tag[..tag.len() - 1]);
+            xhtml.push_str("/>");
+        } else {
+            xhtml.push_str(tag);
+        }
+        rest = &rest[end..];
+    }
+    xhtml.push_str(rest);
+    xhtml.replace("&nbsp;", "&#160;")
+}
+
+// O Markdown aceita HTML embutido: tudo passa pela lista de tags e atributos
+// permitidos do ammonia (sem scripts, eventos `on*` ou URLs `javascript:`)
+fn sanitizer(id_prefix: &str) -> Builder<'_> {
+    let fragment_prefix = format!("#{}", id_prefix);
+    let mut builder = Builder::default();
+    builder
+        .add_tag_attributes("div", &["id"])
+        .add_tag_attributes("th", &["style"])
+        .add_tag_attributes("td", &["style"])
+        .filter_style_properties(HashSet::from(["text-align"]))
+        .add_allowed_classes("sup", &["footnote-reference", "footnote-definition-label"])
+        .add_allowed_classes("div", &["footnote-definition"])
+        .id_prefix(Some(id_prefix))
+        // Links internos (#nota) apontam para os ids já prefixados
+        .attribute_filter(move |element, attribute, value| match (element, attribute) {
+            ("a", "href") if value.len() > 1 && value.starts_with('#') => {
+                Some(Cow::Owned(format!("{}{}", fragment_prefix, &value[1..])))
+            }
+            _ => Some(Cow::Borrowed(value)),
+        });
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_text_drops_markup() {
+        let text = markdown_text("# Título\n\nUm *dois* **três** `quatro`\ncinco [link](http://x).\n\n<b>html</b>\n");
+        assert_eq!(paragraphs(&text), vec![vec!["Título"], vec!["Um dois três quatro cinco link."], vec!["html"]]);
+    }
+
+    #[test]
+    fn markdown_text_keeps_lists_footnotes_and_code() {
+        let text = markdown_text("- a\n- b\n\nNota[^1].\n\n```\nx = 1\ny = 2\n```\n\n[^1]: Fonte.\n");
+        assert_eq!(
+            paragraphs(&text),
+            vec![vec!["• a"], vec!["• b"], vec!["Nota[1]."], vec!["x = 1", "y = 2"], vec!["[1] Fonte."]]
+        );
+    }
+
+    #[test]
+    fn plain_text_is_unchanged() {
+        let content = "# não é título\n*nem ênfase*";
+        assert!(matches!(render_plain_text(content, ContentFormat::Plain), Cow::Borrowed(c) if c == content));
+    }
+
+    #[test]
+    fn xhtml_is_well_formed() {
+        let content = "Linha  \nquebrada&nbsp;aqui\n\n---\n\n![a > b](x.png)\n\n| a |\n|---|\n| 1 |\n\nNota[^n]\n\n[^n]: texto\n";
+        let xhtml = render_xhtml(content, ContentFormat::Markdown, "c1-");
+        assert!(xhtml.contains("<br/>") && xhtml.contains("<hr/>") && xhtml.contains("&#160;"));
+        assert!(roxmltree::Document::parse(&format!("<div>{}</div>", xhtml)).is_ok(), "{}", xhtml);
+
+        let plain = render_xhtml("a\nb", ContentFormat::Plain, "c1-");
+        assert_eq!(plain, "<p>a<br/>\nb</p>\n");
+    }
+
+    #[test]
+    fn xhtml_replaces_images_with_their_alt_text() {
+        let content = "Antes ![a capa](capa.png) depois.\n\n<img src=\"https://exemplo.com/x.png\" alt=\"remota\">\n";
+        let xhtml = render_xhtml(content, ContentFormat::Markdown, "c1-");
+        assert!(!xhtml.contains("<img"), "{}", xhtml);
+        assert!(xhtml.contains("Antes a capa depois."), "{}", xhtml);
+
+        // A leitura em HTML continua mostrando as imagens
+        assert!(render_html(content, ContentFormat::Markdown, "c1-").contains("<img src=\"capa.png\" alt=\"a capa\">"));
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+use crate::database::get_pool;
+
+// Publicar os livros em revisão cuja data de publicação agendada já chegou
+pub async fn publish_scheduled_books() -> Result<u64, sqlx::Error> {
+    let pool = get_pool();
+
+    let result = sqlx::query(
+        r#"
+        UPDATE books SET
+            status = 'published',
+            published_at = scheduled_publish_at,
+            scheduled_publish_at = NULL,
+            updated_at = NOW()
+        WHERE scheduled_publish_at <= NOW() AND status = 'in_review'
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Tarefa em segundo plano que verifica publicações agendadas periodicamente
+pub fn spawn_publisher(interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match publish_scheduled_books().await {
+                Ok(0) => {}
+                Ok(published) => log_info!("{} livro(s) publicado(s) conforme agendamento", published),
+                Err(e) => log_error!("Erro ao publicar livros agendados: {}", e),
+            }
+        }
+    });
+}
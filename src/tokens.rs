@@ -0,0 +1,245 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use uuid::Uuid;
+use crate::{
+    config::settings,
+    models::{JwtConfig, RefreshToken, TokenScope, User},
+    database::get_pool,
+    errors::AppError,
+};
+
+// Prefixo que distingue tokens de acesso pessoal de tokens JWT
+pub const ACCESS_TOKEN_PREFIX: &str = "bdp_";
+
+fn invalid_refresh_token() -> AppError {
+    AppError::unauthorized("INVALID_REFRESH_TOKEN", "Refresh token inválido ou expirado")
+}
+
+// Gerar um token opaco aleatório (256 bits, codificado em hex)
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Hash SHA-256 do token; apenas o hash é salvo no banco
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Emitir um refresh token iniciando uma nova família (uma por login)
+pub async fn issue_refresh_token(user_id: Uuid) -> Result<String, AppError> {
+    let pool = get_pool();
+    let jwt_config = JwtConfig::new();
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::days(jwt_config.refresh_token_days);
+
+    match sqlx::query(
+        "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(user_id)
+    .bind(Uuid::new_v4())
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    {
+        Ok(_) => Ok(token),
+        Err(e) => Err(AppError::internal("Erro ao emitir refresh token", e)),
+    }
+}
+
+// Emitir token de verificação para o email atual do usuário
+pub async fn issue_email_verification_token(user: &User) -> Result<String, AppError> {
+    let pool = get_pool();
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::hours(settings().security.email_verification_hours);
+
+    match sqlx::query(
+        "INSERT INTO email_verification_tokens (user_id, email, token_hash, expires_at) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(user.id)
+    .bind(&user.email)
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    {
+        Ok(_) => Ok(token),
+        Err(e) => Err(AppError::internal("Erro ao emitir token de verificação de email", e)),
+    }
+}
+
+// Emitir o token da segunda etapa do login (contas com 2FA)
+pub async fn issue_mfa_challenge(user_id: Uuid) -> Result<(String, DateTime<Utc>), AppError> {
+    let pool = get_pool();
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::minutes(settings().security.mfa_challenge_minutes);
+
+    match sqlx::query("INSERT INTO mfa_challenges (user_id, token_hash, expires_at) VALUES ($1, $2, $3)")
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .execute(pool)
+        .await
+    {
+        Ok(_) => Ok((token, expires_at)),
+        Err(e) => Err(AppError::internal("Erro ao emitir token de segunda etapa do login", e)),
+    }
+}
+
+// Novo token de acesso pessoal: prefixo seguido de um token opaco
+pub fn generate_access_token() -> String {
+    format!("{}{}", ACCESS_TOKEN_PREFIX, generate_opaque_token())
+}
+
+// Validar um token de acesso pessoal e registrar o uso.
+// Devolve o dono do token e as permissões concedidas.
+pub async fn authenticate_access_token(token: &str) -> Result<(Uuid, Vec<TokenScope>), AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_as::<_, (Uuid, Vec<TokenScope>)>(
+        r#"
+        UPDATE personal_access_tokens SET last_used_at = NOW()
+        WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > NOW())
+        RETURNING user_id, scopes
+        "#
+    )
+    .bind(hash_token(token))
+    .fetch_one(pool)
+    .await
+    {
+        Ok(row) => Ok(row),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::unauthorized("INVALID_TOKEN", "Token de acesso inválido ou expirado")),
+        Err(e) => Err(AppError::internal("Erro ao validar token de acesso pessoal", e)),
+    }
+}
+
+// Trocar um refresh token válido por um novo (rotação).
+// Reutilizar um token já trocado revoga toda a família, pois indica vazamento.
+pub async fn rotate_refresh_token(token: &str) -> Result<(User, String), AppError> {
+    let pool = get_pool();
+    let mut tx = pool.begin().await.map_err(|e| AppError::internal("Erro ao iniciar transação", e))?;
+
+    let stored = match sqlx::query_as::<_, RefreshToken>(
+        "SELECT id, user_id, family_id, expires_at, revoked_at FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE"
+    )
+    .bind(hash_token(token))
+    .fetch_one(&mut *tx)
+    .await
+    {
+        Ok(stored) => stored,
+        Err(sqlx::Error::RowNotFound) => return Err(invalid_refresh_token()),
+        Err(e) => return Err(AppError::internal("Erro ao buscar refresh token", e)),
+    };
+
+    if stored.revoked_at.is_some() {
+        // Token reutilizado: revogar a família inteira
+        drop(tx);
+        revoke_family(stored.family_id).await?;
+        log_warn!("Reuso de refresh token detectado para o usuário {}", stored.user_id);
+        return Err(invalid_refresh_token());
+    }
+
+    if stored.expires_at <= Utc::now() {
+        return Err(invalid_refresh_token());
+    }
+
+    let new_token = generate_opaque_token();
+    let jwt_config = JwtConfig::new();
+    let expires_at = Utc::now() + Duration::days(jwt_config.refresh_token_days);
+
+    let result: Result<User, sqlx::Error> = async {
+        let new_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at) VALUES ($1, $2, $3, $4) RETURNING id"
+        )
+        .bind(stored.user_id)
+        .bind(stored.family_id)
+        .bind(hash_token(&new_token))
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW(), replaced_by = $1 WHERE id = $2")
+            .bind(new_id)
+            .bind(stored.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(stored.user_id)
+            .fetch_one(&mut *tx)
+            .await
+    }
+    .await;
+
+    match result {
+        Ok(user) => {
+            tx.commit().await.map_err(|e| AppError::internal("Erro ao confirmar rotação de refresh token", e))?;
+            Ok((user, new_token))
+        }
+        Err(sqlx::Error::RowNotFound) => Err(invalid_refresh_token()),
+        Err(e) => Err(AppError::internal("Erro ao rotacionar refresh token", e)),
+    }
+}
+
+// Revogar todos os tokens de uma família (sessão de um dispositivo)
+pub async fn revoke_family(family_id: Uuid) -> Result<(), AppError> {
+    let pool = get_pool();
+
+    match sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL")
+        .bind(family_id)
+        .execute(pool)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(AppError::internal("Erro ao revogar família de refresh tokens", e)),
+    }
+}
+
+// Revogar a sessão à qual o token pertence (logout)
+pub async fn revoke_refresh_token(token: &str) -> Result<(), AppError> {
+    let pool = get_pool();
+
+    match sqlx::query_scalar::<_, Uuid>("SELECT family_id FROM refresh_tokens WHERE token_hash = $1")
+        .bind(hash_token(token))
+        .fetch_one(pool)
+        .await
+    {
+        Ok(family_id) => revoke_family(family_id).await,
+        // Token desconhecido: nada a revogar
+        Err(sqlx::Error::RowNotFound) => Ok(()),
+        Err(e) => Err(AppError::internal("Erro ao buscar refresh token", e)),
+    }
+}
+
+// Revogar todas as sessões do usuário (logout em todos os dispositivos)
+pub async fn revoke_all_for_user(user_id: Uuid) -> Result<u64, AppError> {
+    let pool = get_pool();
+
+    match sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(pool)
+        .await
+    {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(e) => Err(AppError::internal("Erro ao revogar refresh tokens do usuário", e)),
+    }
+}
+
+// Senha trocada: encerrar as sessões abertas e apagar os tokens de acesso
+// pessoal. Roda na mesma transação que grava a nova senha.
+pub async fn revoke_credentials(conn: &mut PgConnection, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("DELETE FROM personal_access_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
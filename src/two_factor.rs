@@ -0,0 +1,64 @@
+// Autenticação em dois fatores: códigos TOTP (RFC 6238) e códigos de recuperação
+use rand::{Rng, RngCore};
+use totp_rs::{Algorithm, TOTP};
+use crate::config::settings;
+
+// 160 bits, tamanho recomendado pela RFC 4226 para HMAC-SHA1
+const SECRET_BYTES: usize = 20;
+const STEP_SECONDS: i64 = 30;
+const DIGITS: usize = 6;
+// Tentativas de código permitidas por token da segunda etapa do login
+pub const MFA_MAX_ATTEMPTS: i32 = 5;
+
+// Códigos de recuperação: letras e números sem caracteres ambíguos (0/o, 1/l/i)
+pub const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_GROUP_LEN: usize = 5;
+
+// Novo segredo aleatório, em base32 sem padding (formato dos aplicativos autenticadores)
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &bytes)
+}
+
+fn totp(secret: &str, account: &str) -> Option<TOTP> {
+    let bytes = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, secret)?;
+    let issuer = settings().security.totp_issuer.clone();
+    TOTP::new(Algorithm::SHA1, DIGITS, 0, STEP_SECONDS as u64, bytes, Some(issuer), account.to_string()).ok()
+}
+
+// URI otpauth:// para cadastrar a conta no aplicativo (geralmente via QR code)
+pub fn provisioning_uri(secret: &str, account: &str) -> Option<String> {
+    totp(secret, account).map(|totp| totp.get_url())
+}
+
+// Passo de tempo em que o código é válido, aceitando um passo de diferença
+// no relógio. Guardar o último passo usado impede reutilizar o mesmo código.
+pub fn matching_step(secret: &str, code: &str, now: i64) -> Option<i64> {
+    if code.len() != DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let totp = totp(secret, "conta")?;
+    let current = now / STEP_SECONDS;
+    [current, current - 1, current + 1]
+        .into_iter()
+        .find(|step| totp.check(code, (step * STEP_SECONDS) as u64))
+}
+
+// Códigos de uso único no formato "xxxxx-xxxxx" (cerca de 49 bits cada)
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    let mut group = || -> String {
+        (0..RECOVERY_GROUP_LEN)
+            .map(|_| RECOVERY_ALPHABET[rng.gen_range(0..RECOVERY_ALPHABET.len())] as char)
+            .collect()
+    };
+    (0..RECOVERY_CODE_COUNT).map(|_| format!("{}-{}", group(), group())).collect()
+}
+
+// Código digitado pelo usuário sem espaços e hífens, em minúsculas (forma
+// usada no hash dos códigos de recuperação)
+pub fn normalize_code(code: &str) -> String {
+    code.chars().filter(|c| !c.is_whitespace() && *c != '-').collect::<String>().to_lowercase()
+}
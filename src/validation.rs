@@ -0,0 +1,237 @@
+use rocket::{
+    data::{self, Data, FromData},
+    request::Request,
+    serde::json::{self, Json},
+    outcome::Outcome,
+};
+use serde::Deserialize;
+use std::borrow::Cow;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+use crate::{
+    config::settings,
+    errors::{guard_error, AppError},
+    models::FieldError,
+};
+
+// Limite do bcrypt: bytes além de 72 são ignorados no hash
+const PASSWORD_MAX_BYTES: usize = 72;
+
+// Corpo JSON desserializado e validado; substitui `Json<T>` nos handlers.
+// Falhas de validação respondem 422 com a lista de erros por campo.
+#[derive(Debug)]
+pub struct Valid<T>(pub T);
+
+impl<T> Valid<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r> + Validate> FromData<'r> for Valid<T> {
+    type Error = ();
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        match Json::<T>::from_data(request, data).await {
+            Outcome::Success(value) => {
+                let value = value.into_inner();
+                match value.validate() {
+                    Ok(()) => Outcome::Success(Valid(value)),
+                    Err(errors) => Outcome::Error(guard_error(request, validation_error(&errors))),
+                }
+            }
+            Outcome::Error((_, json::Error::Parse(_, e))) => {
+                let error = AppError::unprocessable("INVALID_BODY", format!("Corpo da requisição inválido: {}", e));
+                Outcome::Error(guard_error(request, error))
+            }
+            Outcome::Error((status, _)) => Outcome::Error((status, ())),
+            Outcome::Forward(forward) => Outcome::Forward(forward),
+        }
+    }
+}
+
+// Converter os erros do validator em erros por campo da API
+pub fn validation_error(errors: &ValidationErrors) -> AppError {
+    let mut fields = Vec::new();
+    collect_field_errors("", errors, &mut fields);
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+
+    AppError::unprocessable("VALIDATION_FAILED", "Dados inválidos").with_fields(fields)
+}
+
+fn collect_field_errors(prefix: &str, errors: &ValidationErrors, fields: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                fields.extend(field_errors.iter().map(|error| FieldError {
+                    field: path.clone(),
+                    code: error.code.to_string(),
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| "Valor inválido".to_string()),
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(&path, nested, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(&format!("{}[{}]", path, index), nested, fields);
+                }
+            }
+        }
+    }
+}
+
+fn invalid(code: &'static str, message: impl Into<Cow<'static, str>>) -> ValidationError {
+    ValidationError::new(code).with_message(message.into())
+}
+
+// Campo obrigatório que não pode conter apenas espaços
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(invalid("blank", "Não pode ficar em branco"));
+    }
+    Ok(())
+}
+
+// Senha com o tamanho mínimo configurado em [security] e o limite do bcrypt
+pub fn password(value: &str) -> Result<(), ValidationError> {
+    let min_length = settings().security.password_min_length;
+    if value.chars().count() < min_length {
+        return Err(invalid(
+            "too_short",
+            format!("A senha deve ter pelo menos {} caracteres", min_length),
+        ));
+    }
+    if value.len() > PASSWORD_MAX_BYTES {
+        return Err(invalid(
+            "too_long",
+            format!("A senha deve ter no máximo {} bytes", PASSWORD_MAX_BYTES),
+        ));
+    }
+    Ok(())
+}
+
+// Formato de página conhecido pela exportação em PDF
+pub fn trim_size(value: &str) -> Result<(), ValidationError> {
+    if crate::export::pdf::trim_size(value).is_none() {
+        let names: Vec<&str> = crate::export::pdf::TRIM_SIZES.iter().map(|(name, _, _)| *name).collect();
+        return Err(invalid("trim_size", format!("Formato inválido (use {})", names.join(", "))));
+    }
+    Ok(())
+}
+
+// ISBN-10 ou ISBN-13 com dígito verificador válido (hífens e espaços são ignorados)
+pub fn isbn(value: &str) -> Result<(), ValidationError> {
+    let chars: Vec<char> = value.chars().filter(|c| *c != '-' && *c != ' ').collect();
+
+    let valid = match chars.len() {
+        10 => isbn10_checksum(&chars),
+        13 => isbn13_checksum(&chars),
+        _ => false,
+    };
+
+    if !valid {
+        return Err(invalid("isbn", "ISBN inválido (esperado ISBN-10 ou ISBN-13)"));
+    }
+    Ok(())
+}
+
+fn isbn10_checksum(chars: &[char]) -> bool {
+    let mut sum = 0;
+    for (i, c) in chars.iter().enumerate() {
+        let digit = match (c.to_digit(10), i) {
+            (Some(d), _) => d,
+            // 'X' vale 10 e só é aceito como dígito verificador
+            (None, 9) if *c == 'X' || *c == 'x' => 10,
+            _ => return false,
+        };
+        sum += digit * (10 - i as u32);
+    }
+    sum % 11 == 0
+}
+
+fn isbn13_checksum(chars: &[char]) -> bool {
+    let mut sum = 0;
+    for (i, c) in chars.iter().enumerate() {
+        let Some(digit) = c.to_digit(10) else {
+            return false;
+        };
+        sum += if i % 2 == 0 { digit } else { digit * 3 };
+    }
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isbn10_with_valid_checksum() {
+        assert!(isbn("0306406152").is_ok());
+        assert!(isbn("0-306-40615-2").is_ok());
+        assert!(isbn("0 306 40615 2").is_ok());
+    }
+
+    #[test]
+    fn isbn10_accepts_x_only_as_check_digit() {
+        assert!(isbn("080442957X").is_ok());
+        assert!(isbn("0-8044-2957-x").is_ok());
+        assert!(isbn("08044X9575").is_err());
+    }
+
+    #[test]
+    fn isbn10_with_invalid_checksum() {
+        assert!(isbn("0306406153").is_err());
+        assert!(isbn("080442957 0").is_err());
+    }
+
+    #[test]
+    fn isbn13_with_valid_checksum() {
+        assert!(isbn("9780306406157").is_ok());
+        assert!(isbn("978-0-306-40615-7").is_ok());
+        assert!(isbn("978 0 345 39180 3").is_ok());
+    }
+
+    #[test]
+    fn isbn13_with_invalid_checksum() {
+        assert!(isbn("9780306406158").is_err());
+        assert!(isbn("978-0-306-40615-X").is_err());
+    }
+
+    #[test]
+    fn isbn_ignores_stray_separators_but_not_other_characters() {
+        assert!(isbn("--978-0306-406157--").is_ok());
+        assert!(isbn("  0306406152 ").is_ok());
+        assert!(isbn("978.0.306.40615.7").is_err());
+        assert!(isbn("").is_err());
+        assert!(isbn("030640615").is_err());
+    }
+
+    #[test]
+    fn password_respects_minimum_length_and_bcrypt_limit() {
+        crate::config::init_test_settings();
+        let min_length = settings().security.password_min_length;
+
+        assert_eq!(password(&"a".repeat(min_length - 1)).unwrap_err().code, "too_short");
+        assert!(password(&"a".repeat(min_length)).is_ok());
+        assert!(password(&"a".repeat(PASSWORD_MAX_BYTES)).is_ok());
+        assert_eq!(password(&"a".repeat(PASSWORD_MAX_BYTES + 1)).unwrap_err().code, "too_long");
+        // O mínimo conta caracteres; o limite do bcrypt conta bytes
+        assert!(password(&"é".repeat(min_length)).is_ok());
+        assert_eq!(password(&"é".repeat(PASSWORD_MAX_BYTES / 2 + 1)).unwrap_err().code, "too_long");
+    }
+
+    #[test]
+    fn not_blank_rejects_whitespace_only() {
+        assert!(not_blank("texto").is_ok());
+        assert_eq!(not_blank(" \t\n").unwrap_err().code, "blank");
+    }
+}
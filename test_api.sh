@@ -21,9 +21,11 @@ test_endpoint() {
     if [ -n "$data" ]; then
         response=$(curl -s -X $method "$BASE_URL$endpoint" \
             -H "Content-Type: application/json" \
+            -H "Authorization: Bearer $TOKEN" \
             -d "$data")
     else
-        response=$(curl -s -X $method "$BASE_URL$endpoint")
+        response=$(curl -s -X $method "$BASE_URL$endpoint" \
+            -H "Authorization: Bearer $TOKEN")
     fi
     
     echo "$response" | jq . 2>/dev/null || echo "$response"
@@ -41,6 +43,19 @@ fi
 echo "✅ Servidor está rodando"
 echo ""
 
+# Obter token JWT (rotas de escrita exigem autenticação)
+TOKEN=$(curl -s -X POST "$BASE_URL/login" \
+    -H "Content-Type: application/json" \
+    -d "{\"email\": \"${TEST_EMAIL:-joao@email.com}\", \"password\": \"${TEST_PASSWORD:-senha123}\"}" \
+    | jq -r '.data.token // empty' 2>/dev/null)
+
+if [ -z "$TOKEN" ]; then
+    echo "⚠️  Não foi possível obter token (execute ./test_auth.sh antes)"
+else
+    echo "✅ Token obtido"
+fi
+echo ""
+
 # Teste 1: Página inicial
 test_endpoint "GET" "/" "" "Página inicial"
 
@@ -21,9 +21,11 @@ test_endpoint() {
     if [ -n "$data" ]; then
         response=$(curl -s -X $method "$BASE_URL$endpoint" \
             -H "Content-Type: application/json" \
+            -H "Authorization: Bearer $TOKEN" \
             -d "$data")
     else
-        response=$(curl -s -X $method "$BASE_URL$endpoint")
+        response=$(curl -s -X $method "$BASE_URL$endpoint" \
+            -H "Authorization: Bearer $TOKEN")
     fi
     
     echo "$response" | jq . 2>/dev/null || echo "$response"
@@ -70,6 +72,13 @@ test_endpoint "POST" "/login" '{
     "password": "senha123"
 }' "Fazer login"
 
+# Verificar token obtido no login
+TOKEN=$(curl -s -X POST "$BASE_URL/login" \
+    -H "Content-Type: application/json" \
+    -d '{"email": "joao@email.com", "password": "senha123"}' \
+    | jq -r '.data.token // empty' 2>/dev/null)
+test_endpoint "POST" "/verify-token" "" "Verificar token (deve retornar o usuário)"
+
 # Teste 6: Tentar login com senha errada
 test_endpoint "POST" "/login" '{
     "email": "joao@email.com",
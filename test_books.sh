@@ -21,9 +21,11 @@ test_endpoint() {
     if [ -n "$data" ]; then
         response=$(curl -s -X $method "$BASE_URL$endpoint" \
             -H "Content-Type: application/json" \
+            -H "Authorization: Bearer $TOKEN" \
             -d "$data")
     else
-        response=$(curl -s -X $method "$BASE_URL$endpoint")
+        response=$(curl -s -X $method "$BASE_URL$endpoint" \
+            -H "Authorization: Bearer $TOKEN")
     fi
     
     echo "$response" | jq . 2>/dev/null || echo "$response"
@@ -41,6 +43,24 @@ fi
 echo "✅ Serviço está rodando"
 echo ""
 
+# Obter token (rotas de escrita exigem autenticação). Com BOOKS_API_TOKEN, usa
+# um token de acesso pessoal (permissões books:read e books:write) sem guardar senha
+if [ -n "$BOOKS_API_TOKEN" ]; then
+    TOKEN=$BOOKS_API_TOKEN
+else
+    TOKEN=$(curl -s -X POST "$BASE_URL/login" \
+        -H "Content-Type: application/json" \
+        -d "{\"email\": \"${TEST_EMAIL:-joao@email.com}\", \"password\": \"${TEST_PASSWORD:-senha123}\"}" \
+        | jq -r '.data.token // empty' 2>/dev/null)
+fi
+
+if [ -z "$TOKEN" ]; then
+    echo "⚠️  Não foi possível obter token (execute ./test_auth.sh antes)"
+else
+    echo "✅ Token obtido"
+fi
+echo ""
+
 # Teste 1: Página de livros
 echo "🔍 Acessando página de livros"
 curl -s "$BASE_URL/library" | grep -q "Biblioteca Digital" && echo "✅ Página de livros carregada" || echo "❌ Erro na página de livros"